@@ -0,0 +1,45 @@
+//! Plays a few episodes of `FlappyEnv` with a hand-written policy that flaps
+//! whenever the bird drops below the next gap, and prints what it earned.
+//!
+//! cargo run --example env -- [--episodes 5]
+
+use flappy_bevy::env::{Action, FlappyEnv};
+
+fn main() {
+    let episodes: u64 = std::env::args()
+        .skip_while(|arg| arg != "--episodes")
+        .nth(1)
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(5);
+
+    let mut env = FlappyEnv::new();
+    for seed in 0..episodes {
+        let mut observation = env.reset(seed);
+        let (mut total, mut steps) = (0., 0);
+
+        loop {
+            let action = if observation.bird_y < observation.gap_y - observation.gap_height / 4. {
+                Action::Flap
+            } else {
+                Action::Noop
+            };
+
+            let (next, reward, done) = env.step(action);
+            observation = next;
+            total += reward;
+            steps += 1;
+
+            if done || steps >= 60 * 60 {
+                break;
+            }
+        }
+
+        println!(
+            "seed {}: {} steps, score {}, reward {:.1}",
+            seed,
+            steps,
+            env.score(),
+            total
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use std::time::Duration;
+
+use bevy::{app::AppExit, prelude::*, time::TimeUpdateStrategy};
+use flappy_bevy::{
+    bird::{BirdEvent, Player},
+    pipe::PipeRng,
+    score::ScoreRes,
+    FlappyGamePlugins, GameState,
+};
+
+const ROUNDS: u32 = 3;
+
+fn main() {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / 60.,
+        )))
+        .add_plugins(FlappyGamePlugins)
+        .init_resource::<Rounds>()
+        .add_systems(Update, start.run_if(in_state(GameState::Starting)))
+        .add_systems(OnEnter(GameState::GameOver), restart)
+        .run();
+}
+
+#[derive(Default, Resource)]
+struct Rounds(u32);
+
+fn start(mut writer: EventWriter<BirdEvent>) {
+    writer.send(BirdEvent::Jump(Player::ONE));
+}
+
+fn restart(
+    mut rounds: ResMut<Rounds>,
+    score: Res<ScoreRes>,
+    rng: Res<PipeRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    rounds.0 += 1;
+    println!(
+        "round {} over, seed {}, score {}",
+        rounds.0,
+        rng.seed(),
+        score.0
+    );
+
+    if rounds.0 >= ROUNDS {
+        exit.send(AppExit);
+    } else {
+        next_state.set(GameState::Starting);
+    }
+}
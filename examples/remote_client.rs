@@ -0,0 +1,102 @@
+//! A controller for a game started with `--remote <address>`. It flaps whenever
+//! the bird drops below the next gap and restarts after every crash.
+//!
+//! cargo run --example remote_client -- [--connect 7777] [--runs 3] [--headless]
+//!
+//! With `--headless` the client also runs a windowless game on that address
+//! itself, so a controller can be tested end to end without a window.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    thread,
+    time::Duration,
+};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use flappy_bevy::{
+    remote::{RemoteAddr, RemoteControl},
+    FlappyGamePlugins, TIMESTEP,
+};
+use serde_json::Value;
+
+fn main() -> io::Result<()> {
+    let addr: RemoteAddr = flag("--connect")
+        .unwrap_or("7777".to_string())
+        .parse()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+    let runs: u32 = flag("--runs")
+        .and_then(|runs| runs.parse().ok())
+        .unwrap_or(3);
+
+    if std::env::args().any(|arg| arg == "--headless") {
+        let remote = RemoteControl::bind(addr.clone())?;
+        thread::spawn(move || serve(remote));
+    }
+
+    let (reader, mut writer) = connect(&addr)?;
+    let mut reader = BufReader::new(reader);
+
+    let mut finished = 0;
+    let mut line = String::new();
+    while finished < runs {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            break;
+        }
+
+        let frame: Value = serde_json::from_str(&line)?;
+        let command = match frame["state"].as_str() {
+            Some("GameOver") | Some("LevelComplete") => {
+                finished += 1;
+                println!("run {}: score {}", finished, frame["score"]);
+                "reset"
+            }
+            Some("Starting") => "flap",
+            _ => {
+                let number = |key: &str| frame[key].as_f64().unwrap_or_default();
+                if number("bird_y") < number("gap_y") - number("gap_height") / 4. {
+                    "flap"
+                } else {
+                    "noop"
+                }
+            }
+        };
+
+        writeln!(writer, "{}", command)?;
+    }
+
+    Ok(())
+}
+
+fn connect(addr: &RemoteAddr) -> io::Result<(Box<dyn Read>, Box<dyn Write>)> {
+    match addr {
+        RemoteAddr::Tcp(addr) => {
+            let stream = TcpStream::connect(addr)?;
+            stream.set_nodelay(true)?;
+            Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+        }
+        #[cfg(unix)]
+        RemoteAddr::Unix(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(path)?;
+            Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+        }
+    }
+}
+
+fn serve(remote: RemoteControl) {
+    App::new()
+        .add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            TIMESTEP,
+        )))
+        .insert_resource(remote)
+        .add_plugins(FlappyGamePlugins)
+        .run();
+}
+
+fn flag(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    args.next()
+}
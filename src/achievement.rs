@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{BirdEvent, PlayerRound},
+    collision::CollisionKind,
+    played_by_hand,
+    score::{ScoreEvent, ScoreRes},
+    storage, GameState,
+};
+
+const FILE: &str = "achievements.ron";
+
+/// Unlocks the achievements in `assets/game.achievements.ron` as the game is
+/// played, and keeps the unlocks and the counts behind them in the data
+/// directory. Only rounds played by hand count.
+pub struct AchievementPlugin;
+
+impl Plugin for AchievementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Achievements>()
+            .insert_resource(AchievementProgress::load())
+            .add_event::<AchievementUnlocked>()
+            .add_systems(
+                OnEnter(GameState::Dying),
+                count_crashes.run_if(played_by_hand),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                finish_round.run_if(played_by_hand),
+            )
+            .add_systems(
+                OnEnter(GameState::LevelComplete),
+                finish_round.run_if(played_by_hand),
+            )
+            .add_systems(
+                Update,
+                ((count_flaps, count_score).run_if(played_by_hand), unlock).chain(),
+            );
+
+        // Without an asset server there is nothing to unlock.
+        if app.world.contains_resource::<AssetServer>() {
+            app.add_asset::<Achievements>()
+                .init_asset_loader::<AchievementsLoader>()
+                .add_systems(Startup, init)
+                .add_systems(PreUpdate, apply);
+        }
+    }
+}
+
+/// The achievements there are to unlock, loaded from
+/// `assets/game.achievements.ron`.
+#[derive(Resource, Debug, Clone, PartialEq, Default, Deserialize, TypeUuid, TypePath)]
+#[uuid = "b4e2f0a7-3c1d-4e8b-9a56-0d7c2e9f1b38"]
+#[serde(transparent)]
+pub struct Achievements(pub Vec<Achievement>);
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Achievement {
+    /// Identifies the achievement in the saved unlocks, so it must not change.
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub goal: Goal,
+}
+
+/// What unlocks an achievement.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub enum Goal {
+    /// Pass this many pipes in one round.
+    Score(u32),
+    /// Finish this many rounds.
+    Games(u32),
+    /// Flap this many times in all.
+    Flaps(u32),
+    /// Crash into the first pipe of a round this many times.
+    FirstPipeCrashes(u32),
+    /// Crash into this kind of obstacle this many times.
+    Crashes(CollisionKind, u32),
+}
+
+impl Goal {
+    /// How far `progress` has got towards the goal, and the target.
+    pub fn progress(&self, progress: &AchievementProgress) -> (u32, u32) {
+        match *self {
+            Goal::Score(target) => (progress.best_score, target),
+            Goal::Games(target) => (progress.games, target),
+            Goal::Flaps(target) => (progress.flaps, target),
+            Goal::FirstPipeCrashes(target) => (progress.first_pipe_crashes, target),
+            Goal::Crashes(kind, target) => (
+                progress.crashes.get(&kind).copied().unwrap_or_default(),
+                target,
+            ),
+        }
+    }
+
+    pub fn reached(&self, progress: &AchievementProgress) -> bool {
+        let (current, target) = self.progress(progress);
+        current >= target
+    }
+}
+
+/// Everything counted towards the achievements over all the games played, and
+/// the achievements unlocked so far.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AchievementProgress {
+    /// Ids of the unlocked achievements, in the order they were unlocked.
+    pub unlocked: Vec<String>,
+    pub best_score: u32,
+    pub games: u32,
+    pub flaps: u32,
+    pub first_pipe_crashes: u32,
+    pub crashes: HashMap<CollisionKind, u32>,
+}
+
+impl AchievementProgress {
+    /// Reads the progress from the data directory, starting afresh if there is
+    /// none or it can't be read.
+    pub fn load() -> Self {
+        match storage::load(FILE) {
+            Ok(progress) => progress.unwrap_or_default(),
+            Err(error) => {
+                warn!("failed to load achievements: {}", error);
+                AchievementProgress::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = storage::save(FILE, self) {
+            warn!("failed to save achievements: {}", error);
+        }
+    }
+
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.unlocked.contains(&achievement.id)
+    }
+}
+
+/// Sent once when an achievement is unlocked.
+#[derive(Event, Debug, Clone)]
+pub struct AchievementUnlocked(pub Achievement);
+
+#[derive(Default)]
+struct AchievementsLoader;
+
+impl AssetLoader for AchievementsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let achievements = ron::de::from_bytes::<Achievements>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(achievements));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["achievements.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct AchievementsHandle(Handle<Achievements>);
+
+fn init(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(AchievementsHandle(
+        asset_server.load("game.achievements.ron"),
+    ));
+}
+
+fn apply(
+    mut reader: EventReader<AssetEvent<Achievements>>,
+    handle: Res<AchievementsHandle>,
+    assets: Res<Assets<Achievements>>,
+    mut achievements: ResMut<Achievements>,
+) {
+    for event in reader.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(&handle.0) {
+                    *achievements = loaded.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn count_flaps(mut reader: EventReader<BirdEvent>, mut progress: ResMut<AchievementProgress>) {
+    let flaps = reader.iter().count() as u32;
+    if flaps > 0 {
+        progress.flaps += flaps;
+    }
+}
+
+fn count_score(
+    mut reader: EventReader<ScoreEvent>,
+    score: Res<ScoreRes>,
+    mut progress: ResMut<AchievementProgress>,
+) {
+    if reader.iter().count() > 0 && score.0 > progress.best_score {
+        progress.best_score = score.0;
+    }
+}
+
+/// Counts what each bird crashed into, once the last one has.
+fn count_crashes(round_query: Query<&PlayerRound>, mut progress: ResMut<AchievementProgress>) {
+    for round in round_query.iter() {
+        let Some(cause) = round.cause else {
+            continue;
+        };
+
+        *progress.crashes.entry(cause).or_default() += 1;
+        if cause == CollisionKind::Pipe && round.score == 0 {
+            progress.first_pipe_crashes += 1;
+        }
+    }
+}
+
+/// Counts the round that just ended, and saves the counts.
+fn finish_round(mut progress: ResMut<AchievementProgress>) {
+    progress.games += 1;
+    progress.save();
+}
+
+/// Unlocks every achievement whose goal has been reached.
+fn unlock(
+    achievements: Res<Achievements>,
+    mut progress: ResMut<AchievementProgress>,
+    mut writer: EventWriter<AchievementUnlocked>,
+) {
+    if !progress.is_changed() && !achievements.is_changed() {
+        return;
+    }
+
+    let reached: Vec<Achievement> = achievements
+        .0
+        .iter()
+        .filter(|achievement| {
+            !progress.is_unlocked(achievement) && achievement.goal.reached(&progress)
+        })
+        .cloned()
+        .collect();
+    if reached.is_empty() {
+        return;
+    }
+
+    for achievement in reached {
+        info!("achievement unlocked: {}", achievement.name);
+        progress.unlocked.push(achievement.id.clone());
+        writer.send(AchievementUnlocked(achievement));
+    }
+    progress.save();
+}
@@ -0,0 +1,439 @@
+use std::{fmt, hash::Hash};
+
+use bevy::{input::InputSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{bird::Player, settings::Settings, GameState};
+
+/// Turns keyboard, mouse and gamepad input into `InputAction`s through the
+/// bindings in the `Settings`.
+///
+/// Systems read the actions from `Input<InputAction>` the same way they would
+/// read keys from `Input<KeyCode>`.
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Input<InputAction>>()
+            .init_resource::<Rebinding>()
+            .add_systems(PreUpdate, update.after(InputSystem))
+            .add_systems(OnExit(GameState::Controls), stop_rebinding);
+    }
+}
+
+/// Something the player does, whatever input it is bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    Flap,
+    /// The flaps of the other players in a multiplayer round.
+    Flap2,
+    Flap3,
+    Flap4,
+    Confirm,
+    Pause,
+    Back,
+    /// Shows the high score table on the start screen.
+    Scores,
+    /// Shows the achievements on the start screen.
+    Achievements,
+    /// Shows the lifetime statistics on the start screen.
+    Stats,
+    /// Moves the focus to the previous button of a menu.
+    Up,
+    /// Moves the focus to the next button of a menu.
+    Down,
+    Left,
+    Right,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 14] = [
+        InputAction::Flap,
+        InputAction::Flap2,
+        InputAction::Flap3,
+        InputAction::Flap4,
+        InputAction::Confirm,
+        InputAction::Pause,
+        InputAction::Back,
+        InputAction::Scores,
+        InputAction::Achievements,
+        InputAction::Stats,
+        InputAction::Up,
+        InputAction::Down,
+        InputAction::Left,
+        InputAction::Right,
+    ];
+
+    /// The flap of `player`.
+    pub fn flap(player: Player) -> InputAction {
+        match player.0 {
+            0 => InputAction::Flap,
+            1 => InputAction::Flap2,
+            2 => InputAction::Flap3,
+            _ => InputAction::Flap4,
+        }
+    }
+
+    /// Whether the action is the flap of one of the players, who each have a
+    /// gamepad of their own.
+    pub fn is_flap(self) -> bool {
+        matches!(
+            self,
+            InputAction::Flap | InputAction::Flap2 | InputAction::Flap3 | InputAction::Flap4
+        )
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::Flap => "Flap",
+            InputAction::Flap2 => "Flap P2",
+            InputAction::Flap3 => "Flap P3",
+            InputAction::Flap4 => "Flap P4",
+            InputAction::Confirm => "Confirm",
+            InputAction::Pause => "Pause",
+            InputAction::Back => "Back",
+            InputAction::Scores => "Scores",
+            InputAction::Achievements => "Awards",
+            InputAction::Stats => "Stats",
+            InputAction::Up => "Up",
+            InputAction::Down => "Down",
+            InputAction::Left => "Left",
+            InputAction::Right => "Right",
+        }
+    }
+}
+
+/// One input that triggers an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A button on any gamepad.
+    Gamepad(GamepadButtonType),
+    /// A button on the gamepad with this index, counting the gamepads in the
+    /// order they were connected from 0, so that each player can have one.
+    Pad(usize, GamepadButtonType),
+}
+
+impl Binding {
+    /// Whether two bindings are on the same kind of device.
+    pub fn same_device(self, other: Binding) -> bool {
+        match (self, other) {
+            (Binding::Gamepad(_) | Binding::Pad(..), Binding::Gamepad(_) | Binding::Pad(..)) => {
+                true
+            }
+            _ => std::mem::discriminant(&self) == std::mem::discriminant(&other),
+        }
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(MouseButton::Left) => write!(f, "Click"),
+            Binding::Mouse(button) => write!(f, "Mouse {:?}", button),
+            Binding::Gamepad(button) => write!(f, "Pad {}", PadButton(*button)),
+            Binding::Pad(index, button) => write!(f, "Pad {} {}", index + 1, PadButton(*button)),
+        }
+    }
+}
+
+/// The short name of a gamepad button.
+struct PadButton(GamepadButtonType);
+
+impl fmt::Display for PadButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            GamepadButtonType::LeftTrigger => write!(f, "LB"),
+            GamepadButtonType::LeftTrigger2 => write!(f, "LT"),
+            GamepadButtonType::RightTrigger => write!(f, "RB"),
+            GamepadButtonType::RightTrigger2 => write!(f, "RT"),
+            GamepadButtonType::LeftThumb => write!(f, "LS"),
+            GamepadButtonType::RightThumb => write!(f, "RS"),
+            GamepadButtonType::DPadUp => write!(f, "Up"),
+            GamepadButtonType::DPadDown => write!(f, "Down"),
+            GamepadButtonType::DPadLeft => write!(f, "Left"),
+            GamepadButtonType::DPadRight => write!(f, "Right"),
+            GamepadButtonType::Other(button) => write!(f, "{}", button),
+            button => write!(f, "{:?}", button),
+        }
+    }
+}
+
+/// The inputs bound to every action, as kept in the settings file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub flap: Vec<Binding>,
+    pub flap2: Vec<Binding>,
+    pub flap3: Vec<Binding>,
+    pub flap4: Vec<Binding>,
+    pub confirm: Vec<Binding>,
+    pub pause: Vec<Binding>,
+    pub back: Vec<Binding>,
+    pub scores: Vec<Binding>,
+    pub achievements: Vec<Binding>,
+    pub stats: Vec<Binding>,
+    pub up: Vec<Binding>,
+    pub down: Vec<Binding>,
+    pub left: Vec<Binding>,
+    pub right: Vec<Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            flap: vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Pad(0, GamepadButtonType::South),
+                Binding::Pad(0, GamepadButtonType::LeftTrigger2),
+                Binding::Pad(0, GamepadButtonType::RightTrigger2),
+            ],
+            flap2: Bindings::flap(KeyCode::M, 1),
+            flap3: Bindings::flap(KeyCode::Q, 2),
+            flap4: Bindings::flap(KeyCode::P, 3),
+            confirm: vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Key(KeyCode::Return),
+                Binding::Gamepad(GamepadButtonType::South),
+            ],
+            pause: vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Gamepad(GamepadButtonType::Start),
+            ],
+            back: vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Gamepad(GamepadButtonType::East),
+            ],
+            scores: vec![
+                Binding::Key(KeyCode::H),
+                Binding::Gamepad(GamepadButtonType::North),
+            ],
+            achievements: vec![
+                Binding::Key(KeyCode::A),
+                Binding::Gamepad(GamepadButtonType::West),
+            ],
+            stats: vec![
+                Binding::Key(KeyCode::S),
+                Binding::Gamepad(GamepadButtonType::Select),
+            ],
+            up: vec![
+                Binding::Key(KeyCode::Up),
+                Binding::Gamepad(GamepadButtonType::DPadUp),
+            ],
+            down: vec![
+                Binding::Key(KeyCode::Down),
+                Binding::Gamepad(GamepadButtonType::DPadDown),
+            ],
+            left: vec![
+                Binding::Key(KeyCode::Left),
+                Binding::Gamepad(GamepadButtonType::DPadLeft),
+            ],
+            right: vec![
+                Binding::Key(KeyCode::Right),
+                Binding::Gamepad(GamepadButtonType::DPadRight),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    /// The flap of another player: `key`, or the face and trigger buttons of
+    /// the gamepad at `pad`.
+    fn flap(key: KeyCode, pad: usize) -> Vec<Binding> {
+        vec![
+            Binding::Key(key),
+            Binding::Pad(pad, GamepadButtonType::South),
+            Binding::Pad(pad, GamepadButtonType::LeftTrigger2),
+            Binding::Pad(pad, GamepadButtonType::RightTrigger2),
+        ]
+    }
+
+    pub fn get(&self, action: InputAction) -> &[Binding] {
+        match action {
+            InputAction::Flap => &self.flap,
+            InputAction::Flap2 => &self.flap2,
+            InputAction::Flap3 => &self.flap3,
+            InputAction::Flap4 => &self.flap4,
+            InputAction::Confirm => &self.confirm,
+            InputAction::Pause => &self.pause,
+            InputAction::Back => &self.back,
+            InputAction::Scores => &self.scores,
+            InputAction::Achievements => &self.achievements,
+            InputAction::Stats => &self.stats,
+            InputAction::Up => &self.up,
+            InputAction::Down => &self.down,
+            InputAction::Left => &self.left,
+            InputAction::Right => &self.right,
+        }
+    }
+
+    fn get_mut(&mut self, action: InputAction) -> &mut Vec<Binding> {
+        match action {
+            InputAction::Flap => &mut self.flap,
+            InputAction::Flap2 => &mut self.flap2,
+            InputAction::Flap3 => &mut self.flap3,
+            InputAction::Flap4 => &mut self.flap4,
+            InputAction::Confirm => &mut self.confirm,
+            InputAction::Pause => &mut self.pause,
+            InputAction::Back => &mut self.back,
+            InputAction::Scores => &mut self.scores,
+            InputAction::Achievements => &mut self.achievements,
+            InputAction::Stats => &mut self.stats,
+            InputAction::Up => &mut self.up,
+            InputAction::Down => &mut self.down,
+            InputAction::Left => &mut self.left,
+            InputAction::Right => &mut self.right,
+        }
+    }
+
+    /// What `action` is bound to, as shown on the controls screen.
+    pub fn describe(&self, action: InputAction) -> String {
+        let mut text = String::new();
+        let mut previous: Option<Binding> = None;
+
+        // Buttons of a gamepad share one prefix, as in "Space / Pad 1 South, RT".
+        for &binding in self.get(action) {
+            match (previous, binding) {
+                (Some(Binding::Gamepad(_)), Binding::Gamepad(button)) => {
+                    text += &format!(", {}", PadButton(button));
+                }
+                (Some(Binding::Pad(previous, _)), Binding::Pad(index, button))
+                    if previous == index =>
+                {
+                    text += &format!(", {}", PadButton(button));
+                }
+                (Some(_), _) => text += &format!(" / {}", binding),
+                (None, _) => text += &binding.to_string(),
+            }
+            previous = Some(binding);
+        }
+
+        if text.is_empty() {
+            "Unbound".to_string()
+        } else {
+            text
+        }
+    }
+
+    /// Binds `action` to `binding` in place of everything it was bound to on
+    /// the same kind of device.
+    pub fn rebind(&mut self, action: InputAction, binding: Binding) {
+        let bindings = self.get_mut(action);
+        bindings.retain(|bound| !bound.same_device(binding));
+        bindings.push(binding);
+    }
+}
+
+/// The action waiting for an input to be bound to while the controls screen
+/// listens for one. No actions fire in the meantime.
+#[derive(Resource, Debug, Default)]
+pub struct Rebinding(pub Option<InputAction>);
+
+/// The input that was pressed this frame, if any, for binding to `action`.
+/// Gamepad buttons are bound on the gamepad pressed for a flap, and on any
+/// gamepad otherwise.
+pub fn just_pressed_binding(
+    action: InputAction,
+    keyboard: &Input<KeyCode>,
+    mouse: &Input<MouseButton>,
+    gamepad: &Input<GamepadButton>,
+) -> Option<Binding> {
+    keyboard
+        .get_just_pressed()
+        .next()
+        .map(|key| Binding::Key(*key))
+        .or_else(|| {
+            mouse
+                .get_just_pressed()
+                .next()
+                .map(|button| Binding::Mouse(*button))
+        })
+        .or_else(|| {
+            gamepad.get_just_pressed().next().map(|button| {
+                if action.is_flap() {
+                    Binding::Pad(button.gamepad.id, button.button_type)
+                } else {
+                    Binding::Gamepad(button.button_type)
+                }
+            })
+        })
+}
+
+struct Devices<'a> {
+    keyboard: Option<&'a Input<KeyCode>>,
+    mouse: Option<&'a Input<MouseButton>>,
+    gamepad: Option<(&'a Gamepads, &'a Input<GamepadButton>)>,
+}
+
+impl Devices<'_> {
+    /// Whether `binding` is held down, or if `just`, whether it was pressed
+    /// this frame.
+    fn pressed(&self, binding: Binding, just: bool) -> bool {
+        fn check<T: Copy + Eq + Hash + Send + Sync>(input: &Input<T>, code: T, just: bool) -> bool {
+            if just {
+                input.just_pressed(code)
+            } else {
+                input.pressed(code)
+            }
+        }
+
+        match binding {
+            Binding::Key(key) => self.keyboard.is_some_and(|input| check(input, key, just)),
+            Binding::Mouse(button) => self.mouse.is_some_and(|input| check(input, button, just)),
+            Binding::Gamepad(button) => self.gamepad.is_some_and(|(gamepads, input)| {
+                gamepads
+                    .iter()
+                    .any(|gamepad| check(input, GamepadButton::new(gamepad, button), just))
+            }),
+            Binding::Pad(index, button) => self.gamepad.is_some_and(|(_, input)| {
+                check(input, GamepadButton::new(Gamepad::new(index), button), just)
+            }),
+        }
+    }
+}
+
+fn update(
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    keyboard: Option<Res<Input<KeyCode>>>,
+    mouse: Option<Res<Input<MouseButton>>>,
+    gamepads: Option<Res<Gamepads>>,
+    gamepad: Option<Res<Input<GamepadButton>>>,
+    mut actions: ResMut<Input<InputAction>>,
+) {
+    actions.clear();
+    if rebinding.0.is_some() {
+        actions.release_all();
+        return;
+    }
+
+    let devices = Devices {
+        keyboard: keyboard.as_deref(),
+        mouse: mouse.as_deref(),
+        gamepad: gamepads.as_deref().zip(gamepad.as_deref()),
+    };
+
+    for action in InputAction::ALL {
+        let bindings = settings.bindings.get(action);
+        // Only a fresh press starts an action, so that an input still held
+        // from before, like the one just bound, doesn't fire it.
+        if bindings
+            .iter()
+            .any(|&binding| devices.pressed(binding, true))
+        {
+            actions.press(action);
+        } else if !bindings
+            .iter()
+            .any(|&binding| devices.pressed(binding, false))
+        {
+            actions.release(action);
+        }
+    }
+}
+
+fn stop_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
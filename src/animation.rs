@@ -4,7 +4,7 @@ use bevy::prelude::*;
 
 use crate::GameState;
 
-struct AnimationPlugin;
+pub struct AnimationPlugin;
 
 impl Plugin for AnimationPlugin {
     fn build(&self, app: &mut App) {
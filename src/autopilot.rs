@@ -0,0 +1,182 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    bird::{Bird, BirdEvent, PendingJump, Player, PlayerInput},
+    difficulty::Difficulty,
+    pipe::{Gap, Pipe},
+    rules::GameRules,
+    GameState, TickSet,
+};
+
+/// Flies the bird by itself, threading each gap on the way to the next one.
+///
+/// Decisions are made once per fixed tick from what the bird looked like
+/// `reaction_delay` ticks earlier, and every gap is aimed at with a random
+/// offset of up to `aim_noise` units, so the autopilot can be made to play
+/// more like a person.
+#[derive(Clone, Copy, Default)]
+pub struct AutopilotPlugin {
+    pub reaction_delay: usize,
+    pub aim_noise: f32,
+    pub seed: u64,
+}
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Autopilot::new(*self))
+            .configure_set(
+                Update,
+                PlayerInput.run_if(not(resource_exists::<Autopilot>())),
+            )
+            .add_systems(OnEnter(GameState::Starting), reset)
+            .add_systems(
+                FixedUpdate,
+                (
+                    launch.run_if(in_state(GameState::Starting)),
+                    update.run_if(in_state(GameState::Playing)),
+                )
+                    .in_set(TickSet::Input),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct Autopilot {
+    reaction_delay: usize,
+    aim_noise: f32,
+    seed: u64,
+    rng: StdRng,
+    history: VecDeque<Observation>,
+    /// Lower pipe of the gap currently aimed at, and the aim offset.
+    target: Option<(Entity, f32)>,
+}
+
+impl Autopilot {
+    fn new(plugin: AutopilotPlugin) -> Self {
+        Autopilot {
+            reaction_delay: plugin.reaction_delay,
+            aim_noise: plugin.aim_noise.abs(),
+            seed: plugin.seed,
+            rng: StdRng::seed_from_u64(plugin.seed),
+            history: VecDeque::new(),
+            target: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Observation {
+    y: f32,
+    target: Option<(Entity, f32)>,
+}
+
+fn reset(mut autopilot: ResMut<Autopilot>) {
+    autopilot.rng = StdRng::seed_from_u64(autopilot.seed);
+    autopilot.history.clear();
+    autopilot.target = None;
+}
+
+fn jump(pending: &mut PendingJump, writer: &mut EventWriter<BirdEvent>) {
+    pending.set(Player::ONE);
+    writer.send(BirdEvent::Jump(Player::ONE));
+}
+
+fn launch(mut pending: ResMut<PendingJump>, mut writer: EventWriter<BirdEvent>) {
+    jump(&mut pending, &mut writer);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update(
+    mut autopilot: ResMut<Autopilot>,
+    bird_query: Query<(&Bird, &Player)>,
+    pipe_query: Query<(Entity, &Pipe)>,
+    rules: Res<GameRules>,
+    difficulty: Res<Difficulty>,
+    time: Res<FixedTime>,
+    mut pending: ResMut<PendingJump>,
+    mut writer: EventWriter<BirdEvent>,
+) {
+    let Some(bird) = Player::ONE.bird(&bird_query) else {
+        return;
+    };
+
+    // A jump lifts the bird by this much before it starts falling again, so
+    // jumping this far below the target keeps the arc centered on it.
+    let apex = rules.bird.jump_velocity.powi(2) / (2. * rules.bird.gravity);
+    // Room to keep from the pipes for the arc, for overshooting it by a tick or
+    // two, and for reacting late or aiming off.
+    let late =
+        autopilot.reaction_delay as f32 * rules.bird.jump_velocity * time.period.as_secs_f32();
+    let margin = apex / 2. + Bird::SIZE.y + late + autopilot.aim_noise;
+
+    let y = bird.translation().y;
+    let gaps = Gap::upcoming(
+        pipe_query
+            .iter()
+            .map(|(entity, pipe)| (entity, pipe.translation())),
+    );
+    let mut target = aim(&gaps, margin);
+
+    // If the bird would fall clear of the pipe it is in before it has to jump
+    // again, that pipe no longer matters and the next gap can be aimed at.
+    let next = gaps.get(1..).and_then(|rest| aim(rest, margin));
+    if let Some((current, skip)) = gaps.first().zip(next) {
+        let (_, next) = skip;
+        let bird_left = Bird::X - Bird::SIZE.x / 2.;
+        let time = (current.right() - bird_left) / difficulty.pipe_speed;
+        let (velocity, gravity) = (bird.velocity(), rules.bird.gravity);
+        let lowest = y + (velocity * time - gravity * time * time / 2.).min(0.);
+        let highest = y + velocity.max(0.).powi(2) / (2. * gravity);
+        let half = Bird::SIZE.y / 2.;
+        if lowest > next + autopilot.aim_noise - apex / 2.
+            && lowest - half > current.bottom
+            && highest + half < current.top
+        {
+            target = Some(skip);
+        }
+    }
+
+    autopilot.history.push_back(Observation { y, target });
+
+    if autopilot.history.len() <= autopilot.reaction_delay {
+        return;
+    }
+    let observation = autopilot.history.pop_front().unwrap();
+
+    let target = match (observation.target, autopilot.target) {
+        (Some((pipe, y)), Some((aimed, offset))) if pipe == aimed => y + offset,
+        (Some((pipe, y)), _) => {
+            let noise = autopilot.aim_noise;
+            let offset = if noise > 0. {
+                autopilot.rng.gen_range(-noise..=noise)
+            } else {
+                0.
+            };
+            autopilot.target = Some((pipe, offset));
+            y + offset
+        }
+        (None, _) => 0.,
+    };
+
+    // Below that line the bird keeps jumping, which is also how it climbs fast.
+    if observation.y < target - apex / 2. {
+        jump(&mut pending, &mut writer);
+    }
+}
+
+/// Where to aim in the first of `gaps`: toward the gap after it, as early as
+/// the first one allows while staying `margin` away from its pipes.
+fn aim(gaps: &[Gap], margin: f32) -> Option<(Entity, f32)> {
+    let current = gaps.first()?;
+    let (low, high) = (current.bottom + margin, current.top - margin);
+    let next = gaps.get(1).map_or(current.center(), Gap::center);
+    let y = if low < high {
+        next.clamp(low, high)
+    } else {
+        current.center()
+    };
+    Some((current.pipe, y))
+}
@@ -1,29 +1,38 @@
 use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{difficulty::Difficulty, in_run, rules::GameRules, GameState};
 
 pub struct BasePlugin;
 
 impl Plugin for BasePlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(BaseSpeed(100.))
-            .add_systems(Startup, setup)
+            .init_resource::<BaseTexture>()
+            .add_systems(
+                Startup,
+                (load_texture.run_if(resource_exists::<AssetServer>()), setup).chain(),
+            )
             .add_systems(OnEnter(GameState::Starting), on_enter_starting)
             .add_systems(OnEnter(GameState::Playing), on_enter_playing)
-            .add_systems(Update, update.run_if(not(in_state(GameState::GameOver))));
+            .add_systems(Update, (apply_difficulty, update).run_if(in_run));
     }
 }
 
+/// How fast the base scrolls, and with it the rest of the world.
 #[derive(Resource)]
-pub struct BaseSpeed(f32);
+pub struct BaseSpeed(pub f32);
+
+#[derive(Default, Resource)]
+pub struct BaseTexture(Handle<Image>);
 
 #[derive(Component, Default)]
 pub struct Base;
 
 impl Base {
     pub const SIZE: Vec2 = Vec2::new(336., 112.);
+    pub const Y: f32 = -200.;
 
-    pub fn bundle(asset_server: &Res<AssetServer>, position: Vec2) -> (Self, SpriteBundle) {
+    pub fn bundle(texture: &BaseTexture, position: Vec2) -> (Self, SpriteBundle) {
         (
             Base,
             SpriteBundle {
@@ -31,27 +40,46 @@ impl Base {
                     translation: position.extend(1.0),
                     ..Default::default()
                 },
-                texture: asset_server.load("base.png"),
+                texture: texture.0.clone(),
                 ..Default::default()
             },
         )
     }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let left_position = Vec2::new(0.0, -200.0);
-    let right_position = Vec2::new(0. + Base::SIZE.x, -200.);
+fn load_texture(asset_server: Res<AssetServer>, mut texture: ResMut<BaseTexture>) {
+    texture.0 = asset_server.load("base.png");
+}
+
+fn setup(mut commands: Commands, texture: Res<BaseTexture>) {
+    let left_position = Vec2::new(0.0, Base::Y);
+    let right_position = Vec2::new(0. + Base::SIZE.x, Base::Y);
 
-    commands.spawn(Base::bundle(&asset_server, left_position));
-    commands.spawn(Base::bundle(&asset_server, right_position));
+    commands.spawn(Base::bundle(&texture, left_position));
+    commands.spawn(Base::bundle(&texture, right_position));
 }
 
-fn on_enter_starting(mut speed: ResMut<BaseSpeed>) {
-    speed.0 = 150.0;
+fn on_enter_starting(mut speed: ResMut<BaseSpeed>, rules: Res<GameRules>) {
+    speed.0 = rules.base.starting_speed;
 }
 
-fn on_enter_playing(mut speed: ResMut<BaseSpeed>) {
-    speed.0 = 100.0;
+fn on_enter_playing(mut speed: ResMut<BaseSpeed>, difficulty: Res<Difficulty>) {
+    speed.0 = difficulty.base_speed;
+}
+
+/// Keeps the base scrolling with the pipes as the difficulty changes.
+fn apply_difficulty(
+    mut speed: ResMut<BaseSpeed>,
+    rules: Res<GameRules>,
+    difficulty: Res<Difficulty>,
+    state: Res<State<GameState>>,
+) {
+    if rules.is_changed() || difficulty.is_changed() {
+        speed.0 = match state.get() {
+            GameState::Starting => rules.base.starting_speed,
+            _ => difficulty.base_speed,
+        };
+    }
 }
 
 fn update(speed: Res<BaseSpeed>, mut query: Query<&mut Transform, With<Base>>, time: Res<Time>) {
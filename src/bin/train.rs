@@ -0,0 +1,170 @@
+//! Evolves flappy agents against the headless game.
+//!
+//! Every generation, each genome of the population flies the same seeded pipe
+//! streams in a `FlappyEnv`, and the fittest ones breed the next generation.
+//! The best genome of every generation is written to `--out`.
+//!
+//! cargo run --bin train -- [--population 50] [--generations 30] [--seeds 3]
+//!     [--max-ticks 3600] [--hidden 6] [--seed 0] [--out best.genome.ron]
+//! cargo run --bin train -- --watch best.genome.ron
+
+use std::{fmt, str::FromStr, thread};
+
+use bevy::prelude::*;
+use flappy_bevy::{
+    env::FlappyEnv,
+    neuro::{Genome, NeuralPilotPlugin},
+    windowed::WindowedGamePlugin,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+/// Genomes copied unchanged into the next generation.
+const ELITES: usize = 2;
+/// Genomes drawn for each tournament when picking a parent.
+const TOURNAMENT: usize = 3;
+const MUTATION_RATE: f64 = 0.1;
+const MUTATION_STRENGTH: f32 = 0.5;
+
+fn main() {
+    if let Some(path) = flag::<String>("--watch") {
+        watch(&path);
+        return;
+    }
+
+    let population: usize = flag("--population").unwrap_or(50).max(ELITES + 1);
+    let generations: u32 = flag("--generations").unwrap_or(30);
+    let seeds: u64 = flag("--seeds").unwrap_or(3);
+    let max_ticks: u32 = flag("--max-ticks").unwrap_or(3600);
+    let hidden: usize = flag("--hidden").unwrap_or(6);
+    let out: String = flag("--out").unwrap_or("best.genome.ron".to_string());
+    let mut rng = StdRng::seed_from_u64(flag("--seed").unwrap_or(0));
+
+    let mut genomes: Vec<_> = (0..population)
+        .map(|_| Genome::random(hidden, &mut rng))
+        .collect();
+
+    for generation in 0..generations {
+        // Every genome flies the same pipes, which change every generation so
+        // that no genome gets by on memorizing one layout.
+        let pipe_seeds: Vec<_> = (0..seeds).map(|_| rng.gen()).collect();
+        let mut ranked: Vec<_> = evaluate(&genomes, &pipe_seeds, max_ticks)
+            .into_iter()
+            .zip(genomes)
+            .collect();
+        ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let (best_fitness, best) = &ranked[0];
+        let mean = ranked.iter().map(|(fitness, _)| fitness).sum::<f32>() / ranked.len() as f32;
+        println!(
+            "generation {}: best {:.1}, mean {:.1}",
+            generation, best_fitness, mean
+        );
+        if let Err(error) = best.save(&out) {
+            eprintln!("failed to save genome to {}: {}", out, error);
+            std::process::exit(1);
+        }
+
+        genomes = ranked
+            .iter()
+            .take(ELITES)
+            .map(|(_, genome)| genome.clone())
+            .collect();
+        while genomes.len() < population {
+            let mother = select(&ranked, &mut rng);
+            let father = select(&ranked, &mut rng);
+            let mut child = mother.crossover(father, &mut rng);
+            child.mutate(MUTATION_RATE, MUTATION_STRENGTH, &mut rng);
+            genomes.push(child);
+        }
+    }
+
+    println!("best genome saved to {}", out);
+}
+
+/// Total reward of every genome over the given pipe seeds, spread over one
+/// environment per available core.
+fn evaluate(genomes: &[Genome], seeds: &[u64], max_ticks: u32) -> Vec<f32> {
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+    let chunk = genomes.len().div_ceil(threads);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = genomes
+            .chunks(chunk)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut env = FlappyEnv::new();
+                    chunk
+                        .iter()
+                        .map(|genome| fitness(&mut env, genome, seeds, max_ticks))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().unwrap())
+            .collect()
+    })
+}
+
+fn fitness(env: &mut FlappyEnv, genome: &Genome, seeds: &[u64], max_ticks: u32) -> f32 {
+    let mut total = 0.;
+    for &seed in seeds {
+        let mut observation = env.reset(seed);
+        for _ in 0..max_ticks {
+            let (next, reward, done) = env.step(genome.decide(&observation));
+            observation = next;
+            total += reward;
+            if done {
+                break;
+            }
+        }
+    }
+    total
+}
+
+fn select<'a>(ranked: &'a [(f32, Genome)], rng: &mut impl Rng) -> &'a Genome {
+    let (_, genome) = ranked
+        .choose_multiple(rng, TOURNAMENT)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap();
+    genome
+}
+
+fn watch(path: &str) {
+    let genome = match Genome::load(path) {
+        Ok(genome) => genome,
+        Err(error) => {
+            eprintln!("failed to load genome {}: {}", path, error);
+            std::process::exit(1);
+        }
+    };
+
+    App::new()
+        .add_plugins(WindowedGamePlugin)
+        .add_plugins(NeuralPilotPlugin(genome))
+        .run();
+}
+
+/// Reads the value following `name` on the command line, if the flag is given.
+/// Exits with an error if the value is missing or doesn't parse.
+fn flag<T: FromStr>(name: &str) -> Option<T>
+where
+    T::Err: fmt::Display,
+{
+    let mut args = std::env::args().skip_while(|arg| arg != name);
+    args.next()?;
+    let Some(value) = args.next() else {
+        eprintln!("{} needs a value", name);
+        std::process::exit(1);
+    };
+
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(error) => {
+            eprintln!("invalid {} {}: {}", name, value, error);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,23 +1,62 @@
-use std::path::Path;
-
 use bevy::prelude::*;
 
-const DEFAULT_VELOCITY: f32 = 2.5;
+use crate::{
+    action::InputAction, animation::Animation, base::Base, collision::CollisionKind, in_run,
+    rules::GameRules, settings::Settings, GameState, TickSet,
+};
 
-use crate::{animation::Animation, GameState};
 pub struct BirdPlugin;
 
 impl Plugin for BirdPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<BirdEvent>()
             .init_resource::<FlapSound>()
-            .add_systems(Startup, init)
-            .add_systems(OnEnter(GameState::Starting), spawn)
-            .add_systems(OnExit(GameState::GameOver), despawn.before(spawn))
-            .add_systems(Update, bobble.run_if(in_state(GameState::Starting)))
+            .init_resource::<BirdTextures>()
+            .init_resource::<PendingJump>()
+            .init_resource::<PlayerCount>()
+            .add_systems(Startup, init.run_if(resource_exists::<AssetServer>()))
+            .add_systems(
+                OnEnter(GameState::Starting),
+                (despawn.before(spawn), spawn, clear_pending_jump),
+            )
+            .add_systems(
+                Update,
+                (despawn.before(spawn), spawn)
+                    .run_if(in_state(GameState::Starting))
+                    .run_if(|count: Res<PlayerCount>| count.is_changed() && !count.is_added()),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_jump,
+                    (bobble, start).run_if(in_state(GameState::Starting)),
+                    update.run_if(in_state(GameState::Playing).or_else(in_state(GameState::Dying))),
+                )
+                    .chain()
+                    .in_set(TickSet::Simulate),
+            )
+            .configure_set(Update, PlayerInput.run_if(in_run))
+            .add_systems(
+                Update,
+                (
+                    handle_input.run_if(resource_exists::<Input<InputAction>>()),
+                    queue_jump,
+                )
+                    .chain()
+                    .in_set(PlayerInput),
+            )
             .add_systems(
                 Update,
-                (update, handle_input).run_if(in_state(GameState::Playing)),
+                (
+                    interpolate.run_if(in_run.or_else(in_state(GameState::Dying))),
+                    fade_crashed,
+                ),
+            )
+            .add_systems(
+                Update,
+                play_flap_sound
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(resource_exists::<AssetServer>()),
             );
     }
 }
@@ -25,50 +64,225 @@ impl Plugin for BirdPlugin {
 #[derive(Default, Resource)]
 pub struct FlapSound(Handle<AudioSource>);
 
+#[derive(Default, Resource)]
+pub struct BirdTextures(Vec<Handle<Image>>);
+
+/// Turns `InputAction::Flap` into jumps. Whatever drives the bird in the
+/// player's place disables this set and sets `PendingJump` itself.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerInput;
+
+/// Set for a player when a `BirdEvent::Jump` arrives and applied to their bird
+/// on the next fixed tick.
+#[derive(Default, Resource)]
+pub struct PendingJump([bool; PlayerCount::MAX]);
+
+impl PendingJump {
+    pub fn set(&mut self, player: Player) {
+        self.0[player.0] = true;
+    }
+
+    pub fn is_set(&self, player: Player) -> bool {
+        self.0[player.0]
+    }
+
+    fn take(&mut self, player: Player) -> bool {
+        std::mem::take(&mut self.0[player.0])
+    }
+}
+
+/// Number of birds that fly each round, one per player.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayerCount(pub usize);
+
+impl PlayerCount {
+    pub const MAX: usize = 4;
+
+    pub fn new(count: usize) -> Self {
+        PlayerCount(count.clamp(1, PlayerCount::MAX))
+    }
+
+    pub fn players(self) -> impl Iterator<Item = Player> {
+        (0..self.0).map(Player)
+    }
+}
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        PlayerCount(1)
+    }
+}
+
+/// The player a bird belongs to, counting from zero.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Player(pub usize);
+
+impl Player {
+    /// The only player of a single player round, and the one that the
+    /// autopilots, replays and remote controllers fly.
+    pub const ONE: Player = Player(0);
+
+    /// The bird of this player among `birds`.
+    pub fn bird<'a>(
+        self,
+        birds: impl IntoIterator<Item = (&'a Bird, &'a Player)>,
+    ) -> Option<&'a Bird> {
+        birds
+            .into_iter()
+            .find(|(_, player)| **player == self)
+            .map(|(bird, _)| bird)
+    }
+
+    /// Tells the birds apart; the first one keeps its own colors.
+    pub fn tint(self) -> Color {
+        match self.0 {
+            0 => Color::WHITE,
+            1 => Color::rgb(0.6, 0.8, 1.0),
+            2 => Color::rgb(1.0, 0.6, 0.6),
+            _ => Color::rgb(0.6, 1.0, 0.6),
+        }
+    }
+}
+
+impl std::fmt::Display for Player {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "P{}", self.0 + 1)
+    }
+}
+
+/// How a player's round is going.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerRound {
+    pub score: u32,
+    /// The fixed tick the bird crashed on.
+    pub crashed: Option<u64>,
+    /// What the bird crashed into.
+    pub cause: Option<CollisionKind>,
+    /// Hits taken this round, with lives in the collision rules.
+    pub hits: u32,
+    /// The bird can't be hit again before this fixed tick.
+    pub grace_until: u64,
+}
+
+impl PlayerRound {
+    pub fn alive(&self) -> bool {
+        self.crashed.is_none()
+    }
+}
+
 #[derive(Component)]
 pub struct Bobble(f32);
 
+/// Bird physics state, advanced once per fixed tick. Velocity is in units per
+/// second and rotation in degrees.
 #[derive(Component)]
 pub struct Bird {
+    y: f32,
     velocity: f32,
     rotation: f32,
+    previous_y: f32,
+    previous_rotation: f32,
+    jump: bool,
+    landed: bool,
 }
 
 #[derive(Event)]
 pub enum BirdEvent {
-    Jump,
+    Jump(Player),
 }
 
 impl Bird {
     pub const X: f32 = -60.;
     pub const SIZE: Vec2 = Vec2::new(32.0, 24.0);
+
+    fn new() -> Self {
+        Bird {
+            y: 0.,
+            velocity: 0.,
+            rotation: 0.,
+            previous_y: 0.,
+            previous_rotation: 0.,
+            jump: false,
+            landed: false,
+        }
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(Bird::X, self.y, 0.)
+    }
+
+    /// Vertical velocity, in units per second.
+    pub fn velocity(&self) -> f32 {
+        self.velocity
+    }
+
+    /// Puts the bird back in the middle of the screen, level and at rest, after
+    /// it lost a life.
+    pub fn revive(&mut self) {
+        *self = Bird {
+            jump: self.jump,
+            ..Bird::new()
+        };
+    }
+
+    /// Whether the bird crashed and came to rest on the ground.
+    pub fn landed(&self) -> bool {
+        self.landed
+    }
+
+    /// Nose-up angle the bird is drawn at, in degrees.
+    pub fn rotation(&self) -> f32 {
+        Bird::visual_rotation(self.rotation)
+    }
+
+    fn visual_rotation(rotation: f32) -> f32 {
+        rotation.clamp(-90., 25.)
+    }
 }
 
-fn init(asset_server: Res<AssetServer>, mut flap_sound: ResMut<FlapSound>) {
+fn init(
+    asset_server: Res<AssetServer>,
+    mut flap_sound: ResMut<FlapSound>,
+    mut textures: ResMut<BirdTextures>,
+) {
     let handle = asset_server.load("wing.ogg");
     flap_sound.0 = handle;
-}
 
-fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let animation = Animation::new(vec![
-        asset_server.load(Path::new("yellowbird-midflap.png")),
+    textures.0 = vec![
+        asset_server.load("yellowbird-midflap.png"),
         asset_server.load("yellowbird-upflap.png"),
         asset_server.load("yellowbird-downflap.png"),
-    ]);
+    ];
+}
 
-    commands.spawn((
-        Bird {
-            velocity: 0.,
-            rotation: 0.,
-        },
-        SpriteBundle {
-            texture: animation.current().clone(),
-            transform: Transform::from_xyz(Bird::X, 0.0, 0.0),
-            ..Default::default()
-        },
-        animation,
-        Bobble(0.),
-    ));
+fn spawn(mut commands: Commands, textures: Res<BirdTextures>, count: Res<PlayerCount>) {
+    let frames = if textures.0.is_empty() {
+        vec![Handle::default()]
+    } else {
+        textures.0.clone()
+    };
+
+    for player in count.players() {
+        let animation = Animation::new(frames.clone());
+        commands.spawn((
+            Bird::new(),
+            player,
+            PlayerRound::default(),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: player.tint(),
+                    ..default()
+                },
+                texture: animation.current().clone(),
+                // Later birds fly behind the earlier ones.
+                transform: Transform::from_xyz(Bird::X, 0.0, -0.01 * player.0 as f32),
+                ..Default::default()
+            },
+            animation,
+            // Out of step, so that the birds don't cover each other.
+            Bobble(player.0 as f32 * 1.2),
+        ));
+    }
 }
 
 fn despawn(mut commands: Commands, query: Query<Entity, With<Bird>>) {
@@ -77,51 +291,147 @@ fn despawn(mut commands: Commands, query: Query<Entity, With<Bird>>) {
     }
 }
 
+fn clear_pending_jump(mut pending: ResMut<PendingJump>) {
+    *pending = PendingJump::default();
+}
+
+fn queue_jump(mut pending: ResMut<PendingJump>, mut reader: EventReader<BirdEvent>) {
+    for BirdEvent::Jump(player) in reader.iter() {
+        pending.set(*player);
+    }
+}
+
+fn apply_jump(
+    mut pending: ResMut<PendingJump>,
+    mut query: Query<(&mut Bird, &Player, &PlayerRound)>,
+) {
+    for (mut bird, player, round) in query.iter_mut() {
+        // A crashed bird falls out of the round whatever its player does.
+        if pending.take(*player) && round.alive() {
+            bird.jump = true;
+        }
+    }
+}
+
+fn start(query: Query<&Bird>, mut next_state: ResMut<NextState<GameState>>) {
+    if query.iter().any(|bird| bird.jump) {
+        next_state.set(GameState::Playing);
+    }
+}
+
 fn update(
-    mut commands: Commands,
-    mut query: Query<(&mut Bird, &mut Transform)>,
-    mut reader: EventReader<BirdEvent>,
-    flap_sound: Res<FlapSound>,
+    mut query: Query<(&mut Bird, &PlayerRound)>,
+    time: Res<FixedTime>,
+    rules: Res<GameRules>,
 ) {
-    const GRAVITY: f32 = 0.098;
-    const ROTATION_DELTA: f32 = 1.5;
+    // Crashed birds tumble nose first.
+    const CRASHED_ROTATION_FACTOR: f32 = 4.;
+    let ground = Base::Y + Base::SIZE.y / 2.;
+    let rules = &rules.bird;
+    let delta = time.period.as_secs_f32();
+
+    for (mut bird, round) in query.iter_mut() {
+        bird.previous_y = bird.y;
+        bird.previous_rotation = bird.rotation;
+        if bird.landed {
+            continue;
+        }
+
+        let rotation_speed = if round.alive() {
+            rules.rotation_speed
+        } else {
+            rules.rotation_speed * CRASHED_ROTATION_FACTOR
+        };
+        bird.velocity -= rules.gravity * delta;
+        bird.rotation -= rotation_speed * delta;
+
+        if bird.jump {
+            bird.jump = false;
+
+            bird.velocity = rules.jump_velocity;
+            bird.rotation = rules.jump_rotation;
+        }
+
+        bird.y += bird.velocity * delta;
+
+        // Crashed birds stop on the base, resting on whichever side they are
+        // turned to. The others are left to the collision rules.
+        if !round.alive() {
+            let (sin, cos) = bird.rotation().to_radians().sin_cos();
+            let half_height = (sin * Bird::SIZE.x).abs() / 2. + (cos * Bird::SIZE.y).abs() / 2.;
+            if bird.y <= ground + half_height {
+                bird.y = ground + half_height;
+                bird.velocity = 0.;
+                bird.landed = true;
+            }
+        }
+    }
+}
 
-    let (mut player, mut transform) = query.single_mut();
+/// Places the sprite between the last two fixed ticks, so motion stays smooth
+/// when the frame rate and the tick rate differ.
+fn interpolate(mut query: Query<(&Bird, &mut Transform)>, time: Res<FixedTime>) {
+    let alpha = time.accumulated().as_secs_f32() / time.period.as_secs_f32();
 
-    player.velocity -= GRAVITY;
-    player.rotation -= ROTATION_DELTA;
+    for (bird, mut transform) in query.iter_mut() {
+        let previous = Vec2::new(
+            bird.previous_y,
+            Bird::visual_rotation(bird.previous_rotation),
+        );
+        let current = Vec2::new(bird.y, Bird::visual_rotation(bird.rotation));
+        let pose = previous.lerp(current, alpha);
 
+        transform.translation.y = pose.x;
+        transform.rotation = Quat::from_rotation_z(pose.y.to_radians());
+    }
+}
+
+fn play_flap_sound(
+    mut commands: Commands,
+    mut reader: EventReader<BirdEvent>,
+    flap_sound: Res<FlapSound>,
+    settings: Option<Res<Settings>>,
+) {
     if !reader.is_empty() {
         reader.clear();
 
-        player.velocity = DEFAULT_VELOCITY;
-        player.rotation = 50.;
-
+        let settings = settings
+            .map(|settings| settings.clone())
+            .unwrap_or_default();
         commands.spawn(AudioBundle {
             source: flap_sound.0.clone(),
-            ..default()
+            settings: PlaybackSettings::ONCE.with_volume(settings.sfx(1.0)),
         });
     }
-
-    transform.translation.y += player.velocity;
-    transform.rotation = Quat::from_rotation_z(player.rotation.clamp(-90., 25.).to_radians());
 }
 
 fn handle_input(
-    keyboard: Res<Input<KeyCode>>,
-    mouse: Res<Input<MouseButton>>,
+    actions: Res<Input<InputAction>>,
+    count: Res<PlayerCount>,
     mut writer: EventWriter<BirdEvent>,
 ) {
-    if keyboard.just_pressed(KeyCode::Space) || mouse.just_pressed(MouseButton::Left) {
-        writer.send(BirdEvent::Jump);
+    for player in count.players() {
+        if actions.just_pressed(InputAction::flap(player)) {
+            writer.send(BirdEvent::Jump(player));
+        }
+    }
+}
+
+/// Crashed birds fade as they drop out of the round.
+fn fade_crashed(mut query: Query<(&PlayerRound, &Player, &mut Sprite), Changed<PlayerRound>>) {
+    for (round, player, mut sprite) in query.iter_mut() {
+        let alpha = if round.alive() { 1.0 } else { 0.4 };
+        sprite.color = player.tint().with_a(alpha);
     }
 }
 
-fn bobble(mut query: Query<(&mut Bobble, &mut Transform)>) {
-    for (mut bobble, mut transform) in query.iter_mut() {
-        let y = bobble.0.sin() * 20.;
-        transform.translation.y = y;
+fn bobble(mut query: Query<(&mut Bobble, &mut Bird)>, time: Res<FixedTime>) {
+    const FREQUENCY: f32 = std::f32::consts::PI * 0.6;
+
+    for (mut bobble, mut bird) in query.iter_mut() {
+        bird.previous_y = bird.y;
+        bird.y = bobble.0.sin() * 20.;
 
-        bobble.0 += std::f32::consts::PI / 100.;
+        bobble.0 += FREQUENCY * time.period.as_secs_f32();
     }
 }
@@ -1,54 +1,348 @@
-use bevy::prelude::*;
+use std::collections::HashMap;
 
-use crate::{base::Base, bird::Bird, pipe::Pipe, GameState};
+use bevy::{asset::HandleId, prelude::*, render::render_resource::TextureFormat};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    base::Base,
+    bird::{Bird, PlayerRound},
+    pipe::{Pipe, SCREEN_TOP},
+    rules::GameRules,
+    GameState, SimulationTick, TickSet,
+};
 
 pub struct CollisionPlugin;
 
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, update.run_if(in_state(GameState::Playing)));
+        app.add_event::<CollisionEvent>()
+            .init_resource::<CollisionMasks>()
+            .add_systems(
+                Update,
+                build_masks.run_if(resource_exists::<Assets<Image>>()),
+            )
+            .add_systems(
+                FixedUpdate,
+                (detect, resolve)
+                    .chain()
+                    .in_set(TickSet::Resolve)
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
 
-fn update(
-    mut next_state: ResMut<NextState<GameState>>,
-    bird_query: Query<&Transform, With<Bird>>,
-    pipe_query: Query<&Transform, With<Pipe>>,
-    base_query: Query<&Transform, With<Base>>,
+/// How closely the bird has to touch something to crash into it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum CollisionMode {
+    /// The unrotated boxes of the sprites overlap.
+    Box,
+    /// Opaque pixels of the sprites overlap, with the bird drawn at its
+    /// current rotation. Falls back to the boxes until the images are loaded,
+    /// and when running without them.
+    Pixel,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct CollisionRules {
+    pub mode: CollisionMode,
+    /// Hits a bird takes before it crashes. Losing a life puts it back in the
+    /// middle of the screen.
+    pub lives: u32,
+    /// Ticks after losing a life during which a bird can't be hit again.
+    pub grace_ticks: u64,
+    /// Whether flying off the top of the screen counts as a hit.
+    pub ceiling: bool,
+    /// Birds go through everything and never crash.
+    pub invincible: bool,
+}
+
+/// What a bird ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CollisionKind {
+    Pipe,
+    Ground,
+    Ceiling,
+}
+
+/// A bird touching something on a fixed tick. Whether it crashes is up to the
+/// collision rules.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct CollisionEvent {
+    pub bird: Entity,
+    /// The pipe or base that was hit, or `None` for the ceiling.
+    pub other: Option<Entity>,
+    pub kind: CollisionKind,
+    /// Where the two touch, in world space.
+    pub point: Vec2,
+}
+
+/// Which pixels of an image are solid, read from its alpha channel.
+#[derive(Debug, Clone)]
+pub struct CollisionMask {
+    size: UVec2,
+    solid: Vec<bool>,
+}
+
+impl CollisionMask {
+    /// Pixels at least this opaque are solid, so that soft edges only count
+    /// where they are mostly drawn.
+    const ALPHA_THRESHOLD: u8 = 128;
+
+    /// Reads the mask of an 8-bit RGBA image. Images in other formats are solid
+    /// all over, so they collide like boxes.
+    pub fn from_image(image: &Image) -> Self {
+        let extent = image.texture_descriptor.size;
+        let size = UVec2::new(extent.width, extent.height);
+        let solid = match image.texture_descriptor.format {
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => image
+                .data
+                .chunks_exact(4)
+                .map(|pixel| pixel[3] >= CollisionMask::ALPHA_THRESHOLD)
+                .collect(),
+            format => {
+                warn!("no collision mask for {:?} images, using boxes", format);
+                vec![true; (size.x * size.y) as usize]
+            }
+        };
+
+        CollisionMask { size, solid }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.size.as_vec2()
+    }
+
+    /// Whether the pixel under `point` is solid. The point is relative to the
+    /// center of the sprite, with y up as in the world.
+    pub fn is_solid(&self, point: Vec2, flip_y: bool) -> bool {
+        let half = self.size() / 2.;
+        let x = point.x + half.x;
+        let y = if flip_y {
+            half.y + point.y
+        } else {
+            half.y - point.y
+        };
+        if x < 0. || y < 0. {
+            return false;
+        }
+
+        let (x, y) = (x as u32, y as u32);
+        x < self.size.x && y < self.size.y && self.solid[(y * self.size.x + x) as usize]
+    }
+
+    /// The centers of the solid pixels, relative to the center of the sprite.
+    fn solid_points(&self) -> impl Iterator<Item = Vec2> + '_ {
+        let half = self.size() / 2.;
+        self.solid
+            .iter()
+            .enumerate()
+            .filter(|(_, solid)| **solid)
+            .map(move |(index, _)| {
+                let x = index as u32 % self.size.x;
+                let y = index as u32 / self.size.x;
+                Vec2::new(x as f32 + 0.5 - half.x, half.y - y as f32 - 0.5)
+            })
+    }
+}
+
+/// Masks of the images the bird, the pipes and the base are drawn with.
+#[derive(Resource, Default)]
+pub struct CollisionMasks(HashMap<HandleId, CollisionMask>);
+
+impl CollisionMasks {
+    pub fn get(&self, image: &Handle<Image>) -> Option<&CollisionMask> {
+        self.0.get(&image.id())
+    }
+}
+
+type CollidingSprite = Or<(With<Bird>, With<Pipe>, With<Base>)>;
+
+/// Builds the mask of every image in use by the sprites that collide once it
+/// has loaded, and again whenever it changes.
+fn build_masks(
+    mut reader: EventReader<AssetEvent<Image>>,
+    images: Res<Assets<Image>>,
+    mut masks: ResMut<CollisionMasks>,
+    sprite_query: Query<&Handle<Image>, CollidingSprite>,
 ) {
-    let mut collided = false;
-    let bird_transform = match bird_query.get_single() {
-        Ok(bird_transform) => bird_transform,
-        _ => return,
+    for event in reader.iter() {
+        if let AssetEvent::Modified { handle } | AssetEvent::Removed { handle } = event {
+            masks.0.remove(&handle.id());
+        }
+    }
+
+    for handle in sprite_query.iter() {
+        if masks.0.contains_key(&handle.id()) {
+            continue;
+        }
+        if let Some(image) = images.get(handle) {
+            masks
+                .0
+                .insert(handle.id(), CollisionMask::from_image(image));
+        }
+    }
+}
+
+/// Something a bird can crash into.
+struct Obstacle<'a> {
+    entity: Entity,
+    kind: CollisionKind,
+    translation: Vec3,
+    size: Vec2,
+    image: &'a Handle<Image>,
+    flip_y: bool,
+}
+
+/// Where the boxes centered on `a` and `b` overlap, if they do.
+fn box_contact(a: Vec3, a_size: Vec2, b: Vec3, b_size: Vec2) -> Option<Vec2> {
+    let overlap = Rect::from_center_size(a.truncate(), a_size)
+        .intersect(Rect::from_center_size(b.truncate(), b_size));
+    (!overlap.is_empty()).then(|| overlap.center())
+}
+
+/// Finds where `bird` touches `obstacle`, first with boxes and then, in pixel
+/// mode and once the masks are built, pixel by pixel.
+fn contact(
+    bird: &Bird,
+    bird_image: &Handle<Image>,
+    obstacle: &Obstacle,
+    mode: CollisionMode,
+    masks: &CollisionMasks,
+) -> Option<Vec2> {
+    let translation = bird.translation();
+    let rotation = bird.rotation().to_radians();
+
+    if mode == CollisionMode::Box {
+        return box_contact(translation, Bird::SIZE, obstacle.translation, obstacle.size);
+    }
+
+    let (Some(bird_mask), Some(obstacle_mask)) = (masks.get(bird_image), masks.get(obstacle.image))
+    else {
+        return box_contact(translation, Bird::SIZE, obstacle.translation, obstacle.size);
     };
 
-    for base_transform in base_query.iter() {
-        let collision = bevy::sprite::collide_aabb::collide(
-            bird_transform.translation,
-            Bird::SIZE,
-            base_transform.translation,
-            Base::SIZE,
-        );
+    // The box around the rotated bird.
+    let (sin, cos) = rotation.sin_cos();
+    let size = bird_mask.size();
+    let bounds = Vec2::new(
+        size.x * cos.abs() + size.y * sin.abs(),
+        size.x * sin.abs() + size.y * cos.abs(),
+    );
+    box_contact(
+        translation,
+        bounds,
+        obstacle.translation,
+        obstacle_mask.size(),
+    )?;
 
-        if collision.is_some() {
-            collided = true;
+    let rotation = Mat2::from_angle(rotation);
+    let offset = translation.truncate() - obstacle.translation.truncate();
+    bird_mask
+        .solid_points()
+        .map(|point| rotation * point + offset)
+        .find(|point| obstacle_mask.is_solid(*point, obstacle.flip_y))
+        .map(|point| point + obstacle.translation.truncate())
+}
+
+/// Sends a `CollisionEvent` for everything each bird still in the round
+/// touches, on every tick it touches it.
+fn detect(
+    mut writer: EventWriter<CollisionEvent>,
+    bird_query: Query<(Entity, &Bird, &Handle<Image>, &PlayerRound)>,
+    pipe_query: Query<(Entity, &Pipe, &Handle<Image>, &Sprite)>,
+    base_query: Query<(Entity, &Transform, &Handle<Image>), With<Base>>,
+    rules: Res<GameRules>,
+    masks: Res<CollisionMasks>,
+) {
+    let obstacles: Vec<Obstacle> = base_query
+        .iter()
+        .map(|(entity, transform, image)| Obstacle {
+            entity,
+            kind: CollisionKind::Ground,
+            translation: transform.translation,
+            size: Base::SIZE,
+            image,
+            flip_y: false,
+        })
+        .chain(
+            pipe_query
+                .iter()
+                .map(|(entity, pipe, image, sprite)| Obstacle {
+                    entity,
+                    kind: CollisionKind::Pipe,
+                    translation: pipe.translation(),
+                    size: Pipe::SIZE,
+                    image,
+                    flip_y: sprite.flip_y,
+                }),
+        )
+        .collect();
+
+    for (entity, bird, image, round) in bird_query.iter() {
+        if !round.alive() {
+            continue;
+        }
+
+        for obstacle in obstacles.iter() {
+            if let Some(point) = contact(bird, image, obstacle, rules.collision.mode, &masks) {
+                writer.send(CollisionEvent {
+                    bird: entity,
+                    other: Some(obstacle.entity),
+                    kind: obstacle.kind,
+                    point,
+                });
+            }
+        }
+
+        let top = bird.translation().y + Bird::SIZE.y / 2.;
+        if top > SCREEN_TOP {
+            writer.send(CollisionEvent {
+                bird: entity,
+                other: None,
+                kind: CollisionKind::Ceiling,
+                point: Vec2::new(Bird::X, SCREEN_TOP),
+            });
         }
     }
+}
 
-    for pipe_transform in pipe_query.iter() {
-        let collision = bevy::sprite::collide_aabb::collide(
-            bird_transform.translation,
-            Bird::SIZE,
-            pipe_transform.translation,
-            Pipe::SIZE,
-        );
+/// Decides what the collisions of this tick do to the birds, and ends the
+/// round once no bird is left in it.
+fn resolve(
+    mut reader: EventReader<CollisionEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut bird_query: Query<(&mut Bird, &mut PlayerRound)>,
+    tick: Res<SimulationTick>,
+    rules: Res<GameRules>,
+) {
+    let rules = &rules.collision;
+    let mut crashed = false;
+
+    for event in reader.iter() {
+        if rules.invincible || (event.kind == CollisionKind::Ceiling && !rules.ceiling) {
+            continue;
+        }
+        let Ok((mut bird, mut round)) = bird_query.get_mut(event.bird) else {
+            continue;
+        };
+        // Several things can be hit on the same tick, and a bird that lost a
+        // life needs a moment to get clear of what it hit.
+        if !round.alive() || tick.0 < round.grace_until {
+            continue;
+        }
 
-        if collision.is_some() {
-            collided = true;
+        round.hits += 1;
+        if round.hits >= rules.lives {
+            round.crashed = Some(tick.0);
+            round.cause = Some(event.kind);
+            crashed = true;
+        } else {
+            round.grace_until = tick.0 + rules.grace_ticks;
+            bird.revive();
         }
     }
 
-    if collided {
-        next_state.set(GameState::GameOver);
+    if crashed && bird_query.iter().all(|(_, round)| !round.alive()) {
+        next_state.set(GameState::Dying);
     }
 }
@@ -0,0 +1,143 @@
+use bevy::prelude::*;
+
+use crate::{bird::Bird, settings::Settings, GameState, TickSet};
+
+/// Fixed ticks between the hit and the die sound.
+const DIE_SOUND_TICKS: u32 = 18;
+/// Fixed ticks the crashed birds lie on the ground before the round is over.
+const LANDED_TICKS: u32 = 30;
+
+/// Plays the end of a round: the screen flashes, the sounds play and the birds
+/// fall to the ground before the game over screen comes up.
+pub struct DeathPlugin;
+
+impl Plugin for DeathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DeathSounds>()
+            .init_resource::<DeathSequence>()
+            .add_systems(Startup, init.run_if(resource_exists::<AssetServer>()))
+            .add_systems(OnEnter(GameState::Dying), (start, Flash::spawn))
+            .add_systems(
+                FixedUpdate,
+                advance
+                    .in_set(TickSet::Resolve)
+                    .run_if(in_state(GameState::Dying)),
+            )
+            .add_systems(
+                Update,
+                (
+                    Flash::update,
+                    play_sounds
+                        .run_if(in_state(GameState::Dying))
+                        .run_if(resource_exists::<AssetServer>()),
+                ),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct DeathSounds {
+    hit: Handle<AudioSource>,
+    die: Handle<AudioSource>,
+}
+
+/// How far the death sequence has got.
+#[derive(Resource, Default, Debug)]
+pub struct DeathSequence {
+    /// Fixed ticks since the last bird crashed.
+    pub ticks: u32,
+    /// Fixed ticks since every bird came to rest on the ground.
+    pub landed_ticks: u32,
+    hit_played: bool,
+    die_played: bool,
+}
+
+/// A white flash over the whole screen that fades away.
+#[derive(Component)]
+struct Flash(Timer);
+
+impl Flash {
+    const SECONDS: f32 = 0.3;
+    const SIZE: Vec2 = Vec2::new(288., 512.);
+
+    fn spawn(mut commands: Commands) {
+        commands.spawn((
+            Flash(Timer::from_seconds(Flash::SECONDS, TimerMode::Once)),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::WHITE,
+                    custom_size: Some(Flash::SIZE),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0., 0., 10.),
+                ..default()
+            },
+        ));
+    }
+
+    fn update(
+        mut commands: Commands,
+        mut query: Query<(Entity, &mut Flash, &mut Sprite)>,
+        time: Res<Time>,
+    ) {
+        for (entity, mut flash, mut sprite) in query.iter_mut() {
+            flash.0.tick(time.delta());
+            if flash.0.finished() {
+                commands.entity(entity).despawn();
+            } else {
+                sprite.color.set_a(flash.0.percent_left());
+            }
+        }
+    }
+}
+
+fn init(asset_server: Res<AssetServer>, mut sounds: ResMut<DeathSounds>) {
+    sounds.hit = asset_server.load("hit.wav");
+    sounds.die = asset_server.load("die.wav");
+}
+
+fn start(mut sequence: ResMut<DeathSequence>) {
+    *sequence = DeathSequence::default();
+}
+
+/// Ends the round once the birds have been lying on the ground for a moment.
+fn advance(
+    mut sequence: ResMut<DeathSequence>,
+    bird_query: Query<&Bird>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    sequence.ticks += 1;
+    if bird_query.iter().all(Bird::landed) {
+        sequence.landed_ticks += 1;
+    }
+
+    if sequence.landed_ticks >= LANDED_TICKS {
+        next_state.set(GameState::GameOver);
+    }
+}
+
+fn play_sounds(
+    mut commands: Commands,
+    mut sequence: ResMut<DeathSequence>,
+    sounds: Res<DeathSounds>,
+    settings: Option<Res<Settings>>,
+) {
+    let settings = settings
+        .map(|settings| settings.clone())
+        .unwrap_or_default();
+    let mut play = |sound: &Handle<AudioSource>| {
+        commands.spawn(AudioBundle {
+            source: sound.clone(),
+            settings: PlaybackSettings::ONCE.with_volume(settings.sfx(1.0)),
+        });
+    };
+
+    if !sequence.hit_played {
+        sequence.hit_played = true;
+        play(&sounds.hit);
+    }
+    if !sequence.die_played && sequence.ticks >= DIE_SOUND_TICKS {
+        sequence.die_played = true;
+        play(&sounds.die);
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{rules::GameRules, score::ScoreRes, TickSet};
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .add_systems(FixedUpdate, update.in_set(TickSet::Input));
+    }
+}
+
+/// Named difficulty curves and the one in use, from the `difficulty` section of
+/// the rules asset.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DifficultyRules {
+    pub preset: String,
+    pub presets: HashMap<String, DifficultyCurve>,
+}
+
+impl DifficultyRules {
+    pub fn curve(&self) -> Option<&DifficultyCurve> {
+        self.presets.get(&self.preset)
+    }
+}
+
+/// Multipliers on the base pipe rules, keyed by score. Values between two points
+/// are interpolated linearly and held flat past the last point. An empty curve
+/// leaves the rules unchanged.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(transparent)]
+pub struct DifficultyCurve(pub Vec<DifficultyPoint>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct DifficultyPoint {
+    pub score: u32,
+    pub speed: f32,
+    pub gap: f32,
+    pub interval: f32,
+}
+
+impl DifficultyPoint {
+    pub const fn new(score: u32, speed: f32, gap: f32, interval: f32) -> Self {
+        DifficultyPoint {
+            score,
+            speed,
+            gap,
+            interval,
+        }
+    }
+
+    fn lerp(self, other: DifficultyPoint, t: f32) -> DifficultyPoint {
+        DifficultyPoint {
+            score: other.score,
+            speed: self.speed + (other.speed - self.speed) * t,
+            gap: self.gap + (other.gap - self.gap) * t,
+            interval: self.interval + (other.interval - self.interval) * t,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    pub fn sample(&self, score: u32) -> DifficultyPoint {
+        let points = &self.0;
+        let Some(first) = points.first() else {
+            return DifficultyPoint::new(score, 1., 1., 1.);
+        };
+
+        if score <= first.score {
+            return *first;
+        }
+
+        for window in points.windows(2) {
+            let (from, to) = (window[0], window[1]);
+            if score <= to.score {
+                let t = (score - from.score) as f32 / (to.score - from.score).max(1) as f32;
+                return from.lerp(to, t);
+            }
+        }
+
+        *points.last().unwrap()
+    }
+}
+
+/// Effective pipe and base tuning for the current score.
+#[derive(Resource, Debug, Clone, PartialEq)]
+pub struct Difficulty {
+    pub pipe_speed: f32,
+    pub base_speed: f32,
+    pub gap: f32,
+    pub spawn_interval: f32,
+}
+
+impl Difficulty {
+    pub fn new(rules: &GameRules, score: u32) -> Self {
+        let point = rules
+            .difficulty
+            .curve()
+            .map(|curve| curve.sample(score))
+            .unwrap_or(DifficultyPoint::new(score, 1., 1., 1.));
+
+        Difficulty {
+            pipe_speed: rules.pipe.speed * point.speed,
+            base_speed: rules.base.playing_speed * point.speed,
+            gap: rules.pipe.gap * point.gap,
+            spawn_interval: rules.pipe.spawn_interval * point.interval,
+        }
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::new(&GameRules::default(), 0)
+    }
+}
+
+fn update(mut difficulty: ResMut<Difficulty>, rules: Res<GameRules>, score: Res<ScoreRes>) {
+    if !rules.is_changed() && !score.is_changed() {
+        return;
+    }
+
+    if rules.is_changed() && rules.difficulty.curve().is_none() {
+        warn!("unknown difficulty preset {:?}", rules.difficulty.preset);
+    }
+
+    let next = Difficulty::new(&rules, score.0);
+    if *difficulty != next {
+        *difficulty = next;
+    }
+}
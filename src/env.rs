@@ -0,0 +1,230 @@
+use std::time::Duration;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*, time::TimeUpdateStrategy};
+use serde::Serialize;
+
+use crate::{
+    bird::{Bird, PendingJump, Player},
+    difficulty::Difficulty,
+    pipe::{Gap, Pipe, PipeRng},
+    rules::GameRules,
+    score::{ScoreEvent, ScoreRes},
+    FlappyGamePlugins, GameState, TIMESTEP,
+};
+
+/// A reinforcement learning environment around the real game.
+///
+/// The environment owns a headless app running `FlappyGamePlugins`, and every
+/// `step` advances it by exactly one fixed tick, so agents are trained against
+/// the same systems that run in the game.
+pub struct FlappyEnv {
+    app: App,
+    rewards: Rewards,
+    score_reader: ManualEventReader<ScoreEvent>,
+    done: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Noop,
+    Flap,
+}
+
+/// What the agent sees after every tick, in world units.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Observation {
+    pub bird_y: f32,
+    /// Vertical velocity of the bird, in units per second.
+    pub bird_velocity: f32,
+    /// Horizontal distance from the bird to the center of the next gap. It goes
+    /// negative while the bird is still inside the gap.
+    pub gap_distance: f32,
+    /// Vertical center of the next gap.
+    pub gap_y: f32,
+    /// Distance between the two pipe ends of the next gap.
+    pub gap_height: f32,
+}
+
+impl Observation {
+    /// Observes `bird` and the nearest of the upcoming `gaps`. Until the first
+    /// pipe shows up, a gap is assumed to wait at the spawn point.
+    pub fn new(
+        bird: Option<&Bird>,
+        gaps: &[Gap],
+        rules: &GameRules,
+        difficulty: &Difficulty,
+    ) -> Self {
+        let (gap_distance, gap_y, gap_height) = match gaps.first() {
+            Some(gap) => (gap.x - Bird::X, gap.center(), gap.top - gap.bottom),
+            None => (rules.pipe.spawn_offset - Bird::X, 0., difficulty.gap * 2.),
+        };
+
+        Observation {
+            bird_y: bird.map_or(0., |bird| bird.translation().y),
+            bird_velocity: bird.map_or(0., Bird::velocity),
+            gap_distance,
+            gap_y,
+            gap_height,
+        }
+    }
+
+    pub fn to_array(self) -> [f32; 5] {
+        [
+            self.bird_y,
+            self.bird_velocity,
+            self.gap_distance,
+            self.gap_y,
+            self.gap_height,
+        ]
+    }
+}
+
+/// Reward for each outcome of a tick.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rewards {
+    /// Given for every tick the bird survives.
+    pub alive: f32,
+    /// Given for every pipe the bird passes.
+    pub pipe: f32,
+    /// Given once when the bird hits a pipe or the base.
+    pub crash: f32,
+}
+
+impl Default for Rewards {
+    fn default() -> Self {
+        Rewards {
+            alive: 0.1,
+            pipe: 1.,
+            crash: -1.,
+        }
+    }
+}
+
+impl FlappyEnv {
+    pub fn new() -> Self {
+        FlappyEnv::with_rewards(Rewards::default())
+    }
+
+    pub fn with_rewards(rewards: Rewards) -> Self {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                TIMESTEP,
+            )))
+            .add_plugins(FlappyGamePlugins);
+        // Runs the startup systems and enters `GameState::Starting` without
+        // ticking, so every episode starts from the same place.
+        app.update();
+
+        FlappyEnv {
+            app,
+            rewards,
+            score_reader: ManualEventReader::default(),
+            done: true,
+        }
+    }
+
+    /// The world of the underlying app, to change the `GameRules` or insert a
+    /// `LevelRunner` before the next `reset`.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    /// Starts a new episode with pipes laid out from `seed`.
+    ///
+    /// The bird is launched with its first flap, as a player would, so the
+    /// returned observation is from the first tick of play.
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        self.app.insert_resource(PipeRng::from_seed(seed));
+        self.app
+            .world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Starting);
+        self.app.update();
+
+        while self.state() == Some(GameState::Starting) {
+            self.app
+                .world
+                .resource_mut::<PendingJump>()
+                .set(Player::ONE);
+            self.app.update();
+        }
+
+        self.score_reader
+            .clear(self.app.world.resource::<Events<ScoreEvent>>());
+        self.done = self.state() != Some(GameState::Playing);
+        self.observe()
+    }
+
+    /// Applies `action` and advances the game by one fixed tick. Once `done` is
+    /// returned the episode is over and further steps do nothing until `reset`.
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        if self.done {
+            return (self.observe(), 0., true);
+        }
+
+        if action == Action::Flap {
+            self.app
+                .world
+                .resource_mut::<PendingJump>()
+                .set(Player::ONE);
+        }
+        self.app.update();
+
+        let mut reward = 0.;
+        let events = self.app.world.resource::<Events<ScoreEvent>>();
+        // Both pipes of a pair report the bird passing them on the same tick.
+        if self.score_reader.iter(events).count() > 0 {
+            reward += self.rewards.pipe;
+        }
+
+        // Collisions only queue the state change, so look at the queued state to
+        // end the episode on the tick the bird crashed.
+        let next = self.app.world.resource::<NextState<GameState>>().0;
+        match next.or(self.state()) {
+            Some(GameState::Playing) => reward += self.rewards.alive,
+            Some(GameState::Dying | GameState::GameOver) => {
+                reward += self.rewards.crash;
+                self.done = true;
+            }
+            _ => self.done = true,
+        }
+
+        (self.observe(), reward, self.done)
+    }
+
+    /// Pipes passed in the current episode.
+    pub fn score(&self) -> u32 {
+        self.app.world.resource::<ScoreRes>().0
+    }
+
+    fn state(&self) -> Option<GameState> {
+        self.app
+            .world
+            .get_resource::<State<GameState>>()
+            .map(|state| *state.get())
+    }
+
+    fn observe(&mut self) -> Observation {
+        let world = &mut self.app.world;
+        let pipes: Vec<_> = world
+            .query::<(Entity, &Pipe)>()
+            .iter(world)
+            .map(|(entity, pipe)| (entity, pipe.translation()))
+            .collect();
+        let bird = Player::ONE.bird(world.query::<(&Bird, &Player)>().iter(world));
+
+        Observation::new(
+            bird,
+            &Gap::upcoming(pipes),
+            world.resource::<GameRules>(),
+            world.resource::<Difficulty>(),
+        )
+    }
+}
+
+impl Default for FlappyEnv {
+    fn default() -> Self {
+        FlappyEnv::new()
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::PlayerCount, level::LevelRunner, pipe::PipeRng, played_by_hand, rules::GameRules,
+    score::ScoreRes, storage, GameState,
+};
+
+const FILE: &str = "highscores.ron";
+
+/// Keeps the high score table in the data directory.
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load())
+            .init_resource::<PendingHighScore>()
+            .add_systems(OnEnter(GameState::GameOver), propose.run_if(played_by_hand))
+            .add_systems(OnExit(GameState::GameOver), discard);
+    }
+}
+
+/// A run that made the table and is waiting for the player's name.
+#[derive(Resource, Debug, Default)]
+pub struct PendingHighScore(pub Option<HighScore>);
+
+impl PendingHighScore {
+    /// Enters the pending run into `scores` under `name` and saves the table.
+    pub fn submit(&mut self, name: &str, scores: &mut HighScores) -> Option<usize> {
+        let mut entry = self.0.take()?;
+        entry.name = match name.trim() {
+            "" => "Anonymous".to_string(),
+            name => name.to_string(),
+        };
+
+        let rank = scores.insert(entry);
+        scores.save();
+        rank
+    }
+}
+
+/// The best runs on this machine, highest score first.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HighScores {
+    entries: Vec<HighScore>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub score: u32,
+    /// Day of the run, as `YYYY-MM-DD`.
+    pub date: String,
+    pub seed: u64,
+    pub mode: GameMode,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameMode {
+    /// Random pipes with the named difficulty preset.
+    Endless(String),
+    /// The named hand-authored level.
+    Level(String),
+}
+
+impl GameMode {
+    pub fn current(rules: &GameRules, level: Option<&LevelRunner>) -> Self {
+        match level {
+            Some(runner) => GameMode::Level(runner.level().name.clone()),
+            None => GameMode::Endless(rules.difficulty.preset.clone()),
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameMode::Endless(preset) => write!(f, "endless {}", preset),
+            GameMode::Level(name) => write!(f, "level {}", name),
+        }
+    }
+}
+
+impl HighScores {
+    pub const CAPACITY: usize = 10;
+
+    /// Reads the table from the data directory, starting an empty one if there
+    /// is none or it can't be read.
+    pub fn load() -> Self {
+        match storage::load(FILE) {
+            Ok(scores) => scores.unwrap_or_default(),
+            Err(error) => {
+                warn!("failed to load high scores: {}", error);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = storage::save(FILE, self) {
+            warn!("failed to save high scores: {}", error);
+        }
+    }
+
+    pub fn entries(&self) -> &[HighScore] {
+        &self.entries
+    }
+
+    /// The top score on the table, or 0 while it is empty.
+    pub fn best(&self) -> u32 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    /// Whether `score` would make it onto the table.
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < HighScores::CAPACITY
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    /// Adds `entry` below any earlier entries with the same score and returns
+    /// its rank, or `None` if it didn't make the table.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        if !self.qualifies(entry.score) {
+            return None;
+        }
+
+        let rank = self
+            .entries
+            .iter()
+            .position(|existing| existing.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(rank, entry);
+        self.entries.truncate(HighScores::CAPACITY);
+        Some(rank)
+    }
+}
+
+/// Holds on to a qualifying run until it gets a name. Multiplayer rounds don't
+/// count, and nor do rounds not played by hand, which skip this.
+pub fn propose(
+    mut pending: ResMut<PendingHighScore>,
+    scores: Res<HighScores>,
+    score: Res<ScoreRes>,
+    rng: Res<PipeRng>,
+    rules: Res<GameRules>,
+    level: Option<Res<LevelRunner>>,
+    count: Option<Res<PlayerCount>>,
+) {
+    let solo = count.is_none_or(|count| count.0 == 1);
+    pending.0 = (solo && scores.qualifies(score.0)).then(|| HighScore {
+        name: String::new(),
+        score: score.0,
+        date: storage::today(),
+        seed: rng.seed(),
+        mode: GameMode::current(&rules, level.as_deref()),
+    });
+}
+
+fn discard(mut pending: ResMut<PendingHighScore>) {
+    pending.0 = None;
+}
@@ -0,0 +1,238 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    bird::Bird,
+    difficulty::Difficulty,
+    pipe::{Pipe, PipeTexture},
+    rules::GameRules,
+    GameState, TickSet,
+};
+
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Starting),
+            restart.run_if(resource_exists::<LevelRunner>()),
+        )
+        .add_systems(
+            FixedUpdate,
+            (update.run_if(resource_exists::<LevelRunner>()), oscillate)
+                .in_set(TickSet::Simulate)
+                .run_if(in_state(GameState::Playing)),
+        );
+
+        if app.world.contains_resource::<AssetServer>() {
+            app.add_asset::<Level>()
+                .init_asset_loader::<LevelLoader>()
+                .add_systems(
+                    Startup,
+                    load_selected.run_if(resource_exists::<SelectedLevel>()),
+                )
+                .add_systems(PreUpdate, apply.run_if(resource_exists::<LevelHandle>()));
+        }
+    }
+}
+
+/// A hand-authored sequence of pipe pairs, loaded from a `.level.ron` asset.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "1c9d6a2b-5e47-4f0e-9b3a-7d8e2f4c6a10"]
+pub struct Level {
+    pub name: String,
+    pub obstacles: Vec<Obstacle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Obstacle {
+    /// Horizontal distance from the previous obstacle, or from the spawn point
+    /// for the first one.
+    pub spacing: f32,
+    /// Vertical center of the gap.
+    pub opening: f32,
+    /// Distance from the gap center to each pipe end.
+    pub gap: f32,
+    #[serde(default)]
+    pub movement: Option<Movement>,
+}
+
+/// Vertical oscillation of an obstacle around its opening.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Movement {
+    pub amplitude: f32,
+    /// Seconds for one full up-and-down cycle.
+    pub period: f32,
+}
+
+/// Asset path of the level to play, relative to `assets/`. Insert before the
+/// plugins are built to play a level instead of endless pipes.
+#[derive(Resource)]
+pub struct SelectedLevel(pub String);
+
+/// Spawns the obstacles of a level in place of the random pipe timer.
+///
+/// While this resource exists pipes only come from the level, and the run ends
+/// in `GameState::LevelComplete` once the bird has passed the last obstacle.
+#[derive(Resource)]
+pub struct LevelRunner {
+    level: Level,
+    next: usize,
+    distance: f32,
+}
+
+impl LevelRunner {
+    pub fn new(level: Level) -> Self {
+        let mut runner = LevelRunner {
+            level,
+            next: 0,
+            distance: 0.,
+        };
+        runner.restart();
+        runner
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.level
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.level.obstacles.len()
+    }
+
+    fn restart(&mut self) {
+        self.next = 0;
+        self.distance = self
+            .level
+            .obstacles
+            .first()
+            .map_or(0., |obstacle| obstacle.spacing);
+    }
+}
+
+#[derive(Component)]
+struct Oscillation {
+    origin: f32,
+    movement: Movement,
+    elapsed: f32,
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let level = ron::de::from_bytes::<Level>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(level));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct LevelHandle(Handle<Level>);
+
+fn load_selected(
+    mut commands: Commands,
+    selected: Res<SelectedLevel>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(LevelHandle(asset_server.load(&selected.0)));
+}
+
+fn apply(
+    mut commands: Commands,
+    mut reader: EventReader<AssetEvent<Level>>,
+    handle: Res<LevelHandle>,
+    assets: Res<Assets<Level>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for event in reader.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(level) = assets.get(&handle.0) {
+                    info!("level {:?} loaded", level.name);
+                    commands.insert_resource(LevelRunner::new(level.clone()));
+                    next_state.set(GameState::Starting);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn restart(mut runner: ResMut<LevelRunner>) {
+    runner.restart();
+}
+
+fn update(
+    mut commands: Commands,
+    mut runner: ResMut<LevelRunner>,
+    mut next_state: ResMut<NextState<GameState>>,
+    texture: Res<PipeTexture>,
+    rules: Res<GameRules>,
+    difficulty: Res<Difficulty>,
+    time: Res<FixedTime>,
+) {
+    runner.distance -= difficulty.pipe_speed * time.period.as_secs_f32();
+
+    while runner.distance <= 0. && !runner.finished() {
+        let obstacle = runner.level.obstacles[runner.next].clone();
+        let x = rules.pipe.spawn_offset + runner.distance;
+        let pipes = Pipe::spawn_pair(
+            &mut commands,
+            &texture,
+            Vec2::new(x, obstacle.opening),
+            obstacle.gap,
+            difficulty.pipe_speed,
+        );
+
+        if let Some(movement) = obstacle.movement {
+            for (pipe, offset) in pipes.into_iter().zip([-1., 1.]) {
+                commands.entity(pipe).insert(Oscillation {
+                    origin: obstacle.opening + offset * (obstacle.gap + Pipe::SIZE.y / 2.),
+                    movement,
+                    elapsed: 0.,
+                });
+            }
+        }
+
+        runner.next += 1;
+        if let Some(next) = runner.level.obstacles.get(runner.next) {
+            runner.distance += next.spacing;
+        }
+    }
+
+    // Once the level is exhausted the distance keeps counting down from the
+    // last obstacle, which tells where that obstacle is now.
+    let last_right = rules.pipe.spawn_offset + runner.distance + Pipe::SIZE.x / 2.;
+    if runner.finished() && last_right < Bird::X - Bird::SIZE.x / 2. {
+        next_state.set(GameState::LevelComplete);
+    }
+}
+
+fn oscillate(mut query: Query<(&mut Oscillation, &mut Pipe)>, time: Res<FixedTime>) {
+    for (mut oscillation, mut pipe) in query.iter_mut() {
+        oscillation.elapsed += time.period.as_secs_f32();
+
+        let Movement { amplitude, period } = oscillation.movement;
+        let phase = oscillation.elapsed / period.max(f32::EPSILON) * std::f32::consts::TAU;
+        pipe.set_y(oscillation.origin + amplitude * phase.sin());
+    }
+}
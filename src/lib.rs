@@ -0,0 +1,166 @@
+use bevy::{app::PluginGroupBuilder, prelude::*};
+use serde::Serialize;
+
+pub mod achievement;
+pub mod action;
+pub mod animation;
+pub mod autopilot;
+pub mod base;
+pub mod bird;
+pub mod collision;
+pub mod death;
+pub mod difficulty;
+pub mod env;
+pub mod highscore;
+pub mod level;
+pub mod neuro;
+pub mod parallax;
+pub mod pause;
+pub mod pipe;
+pub mod remote;
+pub mod replay;
+pub mod rules;
+pub mod score;
+pub mod settings;
+pub mod stats;
+pub mod storage;
+pub mod theme;
+pub mod ui;
+pub mod windowed;
+
+use animation::AnimationPlugin;
+use autopilot::Autopilot;
+use base::BasePlugin;
+use bird::BirdPlugin;
+use collision::CollisionPlugin;
+use death::DeathPlugin;
+use difficulty::DifficultyPlugin;
+use level::LevelPlugin;
+use neuro::NeuralPilot;
+use pipe::PipePlugin;
+use remote::{RemoteControl, RemotePlugin};
+use replay::{ReplayMode, ReplayPlugin};
+use rules::RulesPlugin;
+use score::ScorePlugin;
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States, Serialize)]
+pub enum GameState {
+    #[default]
+    Starting,
+    Playing,
+    /// The last bird crashed and falls to the ground. Everything else stays
+    /// frozen.
+    Dying,
+    /// Play is frozen mid-run until it is resumed or restarted.
+    Paused,
+    /// The settings screen, opened from the pause menu. Play stays frozen.
+    Settings,
+    /// The key bindings screen, opened from the settings screen.
+    Controls,
+    GameOver,
+    LevelComplete,
+}
+
+/// Run condition that is true while a run is in progress, from the start
+/// screen until the bird dies or the level ends.
+pub fn in_run(state: Res<State<GameState>>) -> bool {
+    matches!(state.get(), GameState::Starting | GameState::Playing)
+}
+
+/// Run condition that is true while a person flies the bird, rather than a
+/// replay, the autopilot, a trained network or a remote controller. Only their
+/// rounds count towards the high scores, achievements and statistics.
+pub fn played_by_hand(
+    replay: Option<Res<ReplayMode>>,
+    autopilot: Option<Res<Autopilot>>,
+    neural_pilot: Option<Res<NeuralPilot>>,
+    remote: Option<Res<RemoteControl>>,
+) -> bool {
+    !replay::playing_back(replay)
+        && autopilot.is_none()
+        && neural_pilot.is_none()
+        && !remote::controlled(remote)
+}
+
+/// Length of one fixed simulation tick, in seconds.
+pub const TIMESTEP: f32 = 1. / 60.;
+
+/// Number of fixed ticks since the current run entered `GameState::Starting`.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationTick(pub u64);
+
+/// Order of the systems inside one fixed tick.
+///
+/// State transitions are applied at the start of the tick rather than only
+/// once per frame, so that a run plays out the same way whatever the frame rate.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TickSet {
+    Begin,
+    Input,
+    Simulate,
+    Resolve,
+}
+
+pub struct CorePlugin;
+
+impl Plugin for CorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<GameState>()
+            .insert_resource(FixedTime::new_from_secs(TIMESTEP))
+            .init_resource::<SimulationTick>()
+            .configure_sets(
+                FixedUpdate,
+                (
+                    TickSet::Begin,
+                    TickSet::Input,
+                    TickSet::Simulate,
+                    TickSet::Resolve,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_state_transition::<GameState>,
+                    advance_tick.run_if(in_run),
+                )
+                    .chain()
+                    .in_set(TickSet::Begin),
+            )
+            .add_systems(OnEnter(GameState::Starting), reset_tick);
+    }
+}
+
+fn advance_tick(mut tick: ResMut<SimulationTick>) {
+    tick.0 += 1;
+}
+
+fn reset_tick(mut tick: ResMut<SimulationTick>) {
+    tick.0 = 0;
+}
+
+/// The game simulation: bird, pipes, base, collision, death and score.
+///
+/// Runs under `MinimalPlugins` as well as `DefaultPlugins`. Textures, sounds and
+/// input are only hooked up when the `AssetServer` and `Input` resources exist,
+/// so a headless app drives the bird by sending `BirdEvent::Jump` itself.
+pub struct FlappyGamePlugins;
+
+impl PluginGroup for FlappyGamePlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(CorePlugin)
+            .add(RulesPlugin)
+            .add(DifficultyPlugin)
+            .add(AnimationPlugin)
+            .add(BasePlugin)
+            .add(BirdPlugin)
+            .add(PipePlugin)
+            .add(LevelPlugin)
+            .add(CollisionPlugin)
+            .add(DeathPlugin)
+            .add(ScorePlugin)
+            .add(ReplayPlugin)
+            .add(RemotePlugin)
+    }
+}
@@ -1,60 +1,109 @@
-use base::BasePlugin;
-use bevy::{app::AppExit, prelude::*, window::WindowResolution};
-use bird::BirdPlugin;
-use collision::CollisionPlugin;
-use pipe::PipePlugin;
-use score::ScorePlugin;
-use ui::UiPlugin;
-
-mod animation;
-mod base;
-mod bird;
-mod collision;
-mod pipe;
-mod score;
-mod ui;
-
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
-pub enum GameState {
-    #[default]
-    Starting,
-    Playing,
-    GameOver,
-}
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use flappy_bevy::{
+    autopilot::AutopilotPlugin,
+    bird::PlayerCount,
+    level::SelectedLevel,
+    remote::{RemoteAddr, RemoteControl},
+    replay::{Replay, ReplayMode},
+    windowed::WindowedGamePlugin,
+    TIMESTEP,
+};
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resolution: WindowResolution::new(288.0, 440.0),
-                resizable: false,
-                ..Default::default()
-            }),
-            ..Default::default()
-        }))
-        .add_state::<GameState>()
-        .add_plugins(BasePlugin)
-        .add_plugins(BirdPlugin)
-        .add_plugins(PipePlugin)
-        .add_plugins(CollisionPlugin)
-        .add_plugins(ScorePlugin)
-        .add_plugins(UiPlugin)
-        .add_systems(Startup, setup)
-        .add_systems(Update, handle_exit_keypress)
-        .run();
+    let mut app = App::new();
+
+    if let Some(mode) = replay_mode() {
+        app.insert_resource(mode);
+    }
+
+    if let Some(level) = flag_value("--level") {
+        app.insert_resource(SelectedLevel(level));
+    }
+
+    if let Some(players) = flag_value("--players") {
+        match players.parse() {
+            Ok(players) => {
+                app.insert_resource(PlayerCount::new(players));
+            }
+            Err(error) => {
+                eprintln!("invalid player count {}: {}", players, error);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if let Some(remote) = remote_control() {
+        // The controller sets the pace, so every frame advances exactly one tick.
+        app.insert_resource(remote)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                TIMESTEP,
+            )));
+    }
+
+    app.add_plugins(WindowedGamePlugin);
+
+    if std::env::args().any(|arg| arg == "--autopilot") {
+        app.add_plugins(AutopilotPlugin::default());
+    }
+
+    app.run();
+}
+
+/// Reads `--record <path>` or `--replay <path>` from the command line.
+fn replay_mode() -> Option<ReplayMode> {
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--record" => match args.next() {
+                Some(path) => return Some(ReplayMode::record(path)),
+                None => {
+                    eprintln!("--record needs a path to save the replay to");
+                    std::process::exit(1);
+                }
+            },
+            "--replay" => {
+                let Some(path) = args.next() else {
+                    eprintln!("--replay needs the path of a replay to play");
+                    std::process::exit(1);
+                };
+                match Replay::load(&path) {
+                    Ok(replay) => return Some(ReplayMode::playback(replay)),
+                    Err(error) => {
+                        eprintln!("failed to load replay {}: {}", path, error);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
 }
 
-fn handle_exit_keypress(input: Res<Input<KeyCode>>, mut exit: EventWriter<AppExit>) {
-    if input.just_pressed(KeyCode::Escape) {
-        exit.send(AppExit);
+/// Reads `--remote <address>` from the command line and starts listening.
+fn remote_control() -> Option<RemoteControl> {
+    let value = flag_value("--remote")?;
+    let remote = value
+        .parse::<RemoteAddr>()
+        .map_err(|error| error.to_string())
+        .and_then(|addr| RemoteControl::bind(addr).map_err(|error| error.to_string()));
+
+    match remote {
+        Ok(remote) => Some(remote),
+        Err(error) => {
+            eprintln!("failed to listen on {}: {}", value, error);
+            std::process::exit(1);
+        }
     }
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let texture = asset_server.load("background-day.png");
-    commands.spawn(Camera2dBundle::default());
-    commands.spawn(SpriteBundle {
-        texture,
-        ..default()
-    });
+/// Returns the argument following `flag` on the command line, if any.
+fn flag_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != flag);
+    args.next()?;
+    args.next()
 }
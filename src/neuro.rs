@@ -0,0 +1,167 @@
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{Bird, BirdEvent, PendingJump, Player, PlayerInput},
+    difficulty::Difficulty,
+    env::{Action, Observation},
+    pipe::{Gap, Pipe},
+    rules::GameRules,
+    GameState, TickSet,
+};
+
+/// Flies the bird with the network of a trained `Genome`.
+///
+/// Jumps are applied on the tick they are decided, the same way `FlappyEnv`
+/// applies them during training.
+pub struct NeuralPilotPlugin(pub Genome);
+
+impl Plugin for NeuralPilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NeuralPilot(self.0.clone()))
+            .configure_set(
+                Update,
+                PlayerInput.run_if(not(resource_exists::<NeuralPilot>())),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    launch.run_if(in_state(GameState::Starting)),
+                    update.run_if(in_state(GameState::Playing)),
+                )
+                    .in_set(TickSet::Input)
+                    .run_if(resource_exists::<NeuralPilot>()),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct NeuralPilot(pub Genome);
+
+/// Number of network inputs: the scaled observation and a bias.
+const INPUTS: usize = 6;
+
+/// The weights of a network with one hidden layer of `hidden` neurons and a
+/// single output that flaps when positive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    pub hidden: usize,
+    pub weights: Vec<f32>,
+}
+
+impl Genome {
+    pub fn random(hidden: usize, rng: &mut impl Rng) -> Self {
+        let weights = (0..Genome::len(hidden))
+            .map(|_| rng.gen_range(-1.0..=1.0))
+            .collect();
+        Genome { hidden, weights }
+    }
+
+    fn len(hidden: usize) -> usize {
+        hidden * INPUTS + hidden + 1
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Genome> {
+        let genome: Genome = ron::from_str(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+        if genome.weights.len() != Genome::len(genome.hidden) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "genome has the wrong number of weights",
+            ));
+        }
+        Ok(genome)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+
+    /// Takes every weight from either parent at random.
+    pub fn crossover(&self, other: &Genome, rng: &mut impl Rng) -> Genome {
+        let weights = self
+            .weights
+            .iter()
+            .zip(&other.weights)
+            .map(|(a, b)| if rng.gen_bool(0.5) { *a } else { *b })
+            .collect();
+        Genome {
+            hidden: self.hidden,
+            weights,
+        }
+    }
+
+    /// Nudges each weight with probability `rate` by up to `strength`.
+    pub fn mutate(&mut self, rate: f64, strength: f32, rng: &mut impl Rng) {
+        for weight in &mut self.weights {
+            if rng.gen_bool(rate) {
+                *weight += rng.gen_range(-strength..=strength);
+            }
+        }
+    }
+
+    pub fn decide(&self, observation: &Observation) -> Action {
+        // Scaled so that every input stays roughly within -1..1.
+        let inputs: [f32; INPUTS] = [
+            observation.bird_y / 256.,
+            observation.bird_velocity / 400.,
+            observation.gap_distance / 300.,
+            (observation.gap_y - observation.bird_y) / 256.,
+            observation.gap_height / 256.,
+            1.,
+        ];
+
+        let (hidden, output) = self.weights.split_at(self.hidden * INPUTS);
+        let activations = hidden.chunks(INPUTS).map(|weights| {
+            let sum: f32 = weights.iter().zip(&inputs).map(|(w, x)| w * x).sum();
+            sum.tanh()
+        });
+        let sum = output[self.hidden]
+            + activations
+                .zip(output)
+                .map(|(activation, weight)| activation * weight)
+                .sum::<f32>();
+
+        if sum > 0. {
+            Action::Flap
+        } else {
+            Action::Noop
+        }
+    }
+}
+
+fn jump(pending: &mut PendingJump, writer: &mut EventWriter<BirdEvent>) {
+    pending.set(Player::ONE);
+    writer.send(BirdEvent::Jump(Player::ONE));
+}
+
+fn launch(mut pending: ResMut<PendingJump>, mut writer: EventWriter<BirdEvent>) {
+    jump(&mut pending, &mut writer);
+}
+
+fn update(
+    pilot: Res<NeuralPilot>,
+    bird_query: Query<(&Bird, &Player)>,
+    pipe_query: Query<(Entity, &Pipe)>,
+    rules: Res<GameRules>,
+    difficulty: Res<Difficulty>,
+    mut pending: ResMut<PendingJump>,
+    mut writer: EventWriter<BirdEvent>,
+) {
+    let gaps = Gap::upcoming(
+        pipe_query
+            .iter()
+            .map(|(entity, pipe)| (entity, pipe.translation())),
+    );
+    let observation = Observation::new(Player::ONE.bird(&bird_query), &gaps, &rules, &difficulty);
+
+    if pilot.0.decide(&observation) == Action::Flap {
+        jump(&mut pending, &mut writer);
+    }
+}
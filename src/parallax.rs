@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{base::BaseSpeed, in_run};
+
+/// Scrolls the layers of the background, each at its own fraction of the speed
+/// the world goes by at, so that the far ones seem further away.
+pub struct ParallaxPlugin;
+
+impl Plugin for ParallaxPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParallaxScroll>()
+            .add_systems(Update, (scroll.run_if(in_run), place).chain());
+    }
+}
+
+/// One layer of a background, as laid out in a theme.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Layer {
+    /// Asset path of the image, which is as wide as the screen and wraps
+    /// around seamlessly.
+    pub image: String,
+    /// How fast the layer scrolls relative to the pipes and the base. A layer
+    /// at 0 stays put.
+    #[serde(default)]
+    pub speed: f32,
+}
+
+/// How far the world has scrolled by. Every layer is placed from it, so the
+/// layers of two themes line up while one fades into the other.
+#[derive(Resource, Default)]
+pub struct ParallaxScroll(f64);
+
+/// One of the two side by side copies of a layer.
+#[derive(Component)]
+pub struct ParallaxTile {
+    speed: f32,
+    /// 0 for the left copy and 1 for the one after it.
+    slot: u32,
+}
+
+impl ParallaxTile {
+    fn x(&self, scroll: &ParallaxScroll) -> f32 {
+        let offset = (scroll.0 * f64::from(self.speed)).rem_euclid(f64::from(Layer::WIDTH));
+        self.slot as f32 * Layer::WIDTH - offset as f32
+    }
+}
+
+impl Layer {
+    pub const WIDTH: f32 = 288.;
+
+    /// Spawns the two tiles of the layer under `parent`, at `z` above it and
+    /// tinted with `color`.
+    pub fn spawn(
+        &self,
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        scroll: &ParallaxScroll,
+        z: f32,
+        color: Color,
+    ) {
+        let texture = asset_server.load(&self.image);
+        for slot in [0, 1] {
+            let tile = ParallaxTile {
+                speed: self.speed,
+                slot,
+            };
+            let x = tile.x(scroll);
+            parent.spawn((
+                tile,
+                SpriteBundle {
+                    texture: texture.clone(),
+                    sprite: Sprite { color, ..default() },
+                    transform: Transform::from_xyz(x, 0., z),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+fn scroll(speed: Res<BaseSpeed>, mut scroll: ResMut<ParallaxScroll>, time: Res<Time>) {
+    scroll.0 += f64::from(speed.0 * time.delta_seconds());
+}
+
+/// Moves every tile to where the scroll puts it. Each tile wraps around to the
+/// other side of its twin once it is off the screen, like the base does.
+fn place(scroll: Res<ParallaxScroll>, mut query: Query<(&ParallaxTile, &mut Transform)>) {
+    for (tile, mut transform) in query.iter_mut() {
+        transform.translation.x = tile.x(&scroll);
+    }
+}
@@ -0,0 +1,49 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{action::InputAction, remote, GameState};
+
+/// Pauses play with `InputAction::Pause` or when the window loses focus, and
+/// resumes it with `Pause` or `Back`. `Back` on the settings screens goes back
+/// one screen.
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                toggle,
+                // A remote controller keeps the game running in the background.
+                pause_on_focus_loss
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(remote::controlled)),
+            ),
+        );
+    }
+}
+
+fn toggle(
+    actions: Res<Input<InputAction>>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let pause = actions.just_pressed(InputAction::Pause);
+    let back = actions.just_pressed(InputAction::Back);
+
+    match state.get() {
+        GameState::Playing if pause => next_state.set(GameState::Paused),
+        GameState::Paused if pause || back => next_state.set(GameState::Playing),
+        GameState::Settings if back => next_state.set(GameState::Paused),
+        GameState::Controls if back => next_state.set(GameState::Settings),
+        _ => {}
+    }
+}
+
+fn pause_on_focus_loss(
+    mut reader: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if reader.iter().any(|event| !event.focused) {
+        next_state.set(GameState::Paused);
+    }
+}
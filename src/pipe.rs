@@ -1,39 +1,267 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::{bird::Bird, score::ScoreEvent, GameState};
+use crate::{
+    base::Base, bird::Bird, difficulty::Difficulty, in_run, level::LevelRunner, rules::GameRules,
+    score::ScoreEvent, GameState, TickSet,
+};
 
-const SPEED: f32 = 100.0;
 const PIPE_SIZE: Vec2 = Vec2::new(52.0, 320.0);
 const BACKGROUND_SIZE: Vec2 = Vec2::new(288.0, 512.0);
+/// The top edge of the screen.
+pub const SCREEN_TOP: f32 = 220.;
+/// Minimum distance between the gap and the base or the top of the screen.
+const GAP_MARGIN: f32 = 40.;
 
 pub struct PipePlugin;
 
 impl Plugin for PipePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Starting), spawn)
-            .add_systems(OnExit(GameState::GameOver), despawn.after(spawn))
+        app.init_resource::<PipeTexture>()
+            .init_resource::<PipeRng>()
+            .add_systems(Startup, init.run_if(resource_exists::<AssetServer>()))
+            .add_systems(
+                OnEnter(GameState::Starting),
+                (despawn.before(spawn), spawn, reseed),
+            )
+            .add_systems(FixedUpdate, remember_position.in_set(TickSet::Begin))
+            .add_systems(
+                FixedUpdate,
+                (
+                    apply_difficulty,
+                    update_timer.run_if(not(resource_exists::<LevelRunner>())),
+                    update_position,
+                )
+                    .chain()
+                    .in_set(TickSet::Simulate)
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(
                 Update,
-                (update_timer, update_position).run_if(in_state(GameState::Playing)),
+                interpolate.run_if(in_run.or_else(in_state(GameState::Dying))),
             );
     }
 }
 
+#[derive(Default, Resource)]
+pub struct PipeTexture(Handle<Image>);
+
 #[derive(Component)]
 pub struct PipeVelocity(Vec2);
 
+/// Where a pipe is, advanced once per fixed tick. The `Transform` only draws
+/// it, between the last two ticks like the bird.
 #[derive(Component)]
-pub struct Pipe;
+pub struct Pipe {
+    position: Vec2,
+    previous: Vec2,
+}
 
 impl Pipe {
     pub const SIZE: Vec2 = Vec2::new(52.0, 320.0);
+
+    pub fn translation(&self) -> Vec3 {
+        self.position.extend(0.)
+    }
+
+    /// Moves the pipe up or down to `y`, keeping it where it is across.
+    pub fn set_y(&mut self, y: f32) {
+        self.position.y = y;
+    }
+
+    /// Spawns a lower and an upper pipe around a gap centered on `opening`,
+    /// leaving `gap` between the gap center and each pipe end.
+    pub fn spawn_pair(
+        commands: &mut Commands,
+        texture: &PipeTexture,
+        opening: Vec2,
+        gap: f32,
+        speed: f32,
+    ) -> [Entity; 2] {
+        let lower = Vec2::new(opening.x, opening.y - gap - PIPE_SIZE.y / 2.);
+        let upper = Vec2::new(opening.x, opening.y + gap + PIPE_SIZE.y / 2.);
+
+        [
+            commands
+                .spawn(Pipe::bundle(texture, lower, speed, false))
+                .id(),
+            commands
+                .spawn(Pipe::bundle(texture, upper, speed, true))
+                .id(),
+        ]
+    }
+
+    fn bundle(
+        texture: &PipeTexture,
+        position: Vec2,
+        speed: f32,
+        flip_y: bool,
+    ) -> (Self, PipeVelocity, SpriteBundle) {
+        (
+            Pipe {
+                position,
+                previous: position,
+            },
+            PipeVelocity(Vec2::new(-speed, 0.0)),
+            SpriteBundle {
+                transform: Transform {
+                    translation: position.extend(0.),
+                    ..Default::default()
+                },
+                sprite: Sprite {
+                    flip_y,
+                    ..Default::default()
+                },
+                texture: texture.0.clone(),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// The opening between the lower and the upper pipe of a pair.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gap {
+    /// The lower pipe of the pair.
+    pub pipe: Entity,
+    pub x: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Gap {
+    pub fn center(&self) -> f32 {
+        (self.bottom + self.top) / 2.
+    }
+
+    pub fn right(&self) -> f32 {
+        self.x + Pipe::SIZE.x / 2.
+    }
+
+    /// Finds the gaps the bird has not yet flown all the way through, nearest
+    /// first, from the entity and translation of every pipe.
+    pub fn upcoming(pipes: impl IntoIterator<Item = (Entity, Vec3)>) -> Vec<Gap> {
+        let bird_left = Bird::X - Bird::SIZE.x / 2.;
+        let mut pipes: Vec<_> = pipes
+            .into_iter()
+            .filter(|(_, translation)| translation.x + Pipe::SIZE.x / 2. >= bird_left)
+            .collect();
+        pipes.sort_by(|a, b| a.1.x.total_cmp(&b.1.x).then(a.1.y.total_cmp(&b.1.y)));
+
+        // The two pipes of a pair share their x; the lower one ends at the
+        // bottom of the gap.
+        let end = Pipe::SIZE.y / 2.;
+        pipes
+            .chunk_by(|a, b| (a.1.x - b.1.x).abs() < 1.)
+            .map(|pair| {
+                let (pipe, lower) = pair[0];
+                let (_, upper) = pair[pair.len() - 1];
+                Gap {
+                    pipe,
+                    x: lower.x,
+                    bottom: lower.y + end,
+                    top: upper.y - end,
+                }
+            })
+            .collect()
+    }
 }
 
 #[derive(Component)]
 struct PipeTimer(Timer);
 
-fn spawn(mut commands: Commands) {
-    commands.spawn(PipeTimer(Timer::from_seconds(2.0, TimerMode::Repeating)));
+/// Source of randomness for pipe layouts.
+///
+/// The generator restarts from its seed at the start of every run, so a run
+/// can be reproduced exactly by inserting `PipeRng::from_seed` with the same
+/// seed. The default draws a fresh seed for every run instead.
+#[derive(Resource)]
+pub struct PipeRng {
+    seed: u64,
+    fixed: bool,
+    rng: StdRng,
+}
+
+impl PipeRng {
+    pub fn from_seed(seed: u64) -> Self {
+        PipeRng {
+            seed,
+            fixed: true,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Picks the vertical center of the next gap, within bounds that keep both
+    /// pipe ends clear of the base and the top of the screen.
+    pub fn next_opening(&mut self, gap: f32) -> f32 {
+        let min = Base::Y + Base::SIZE.y / 2. + gap + GAP_MARGIN;
+        let max = SCREEN_TOP - gap - GAP_MARGIN;
+
+        if min >= max {
+            return (min + max) / 2.;
+        }
+
+        self.rng.gen_range(min..=max)
+    }
+
+    fn restart(&mut self) {
+        if !self.fixed {
+            self.seed = rand::random();
+        }
+        self.rng = StdRng::seed_from_u64(self.seed);
+    }
+}
+
+impl Default for PipeRng {
+    fn default() -> Self {
+        let seed = rand::random();
+        PipeRng {
+            seed,
+            fixed: false,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+fn init(asset_server: Res<AssetServer>, mut texture: ResMut<PipeTexture>) {
+    texture.0 = asset_server.load("pipe-green.png");
+}
+
+fn spawn(mut commands: Commands, difficulty: Res<Difficulty>) {
+    commands.spawn(PipeTimer(Timer::from_seconds(
+        difficulty.spawn_interval,
+        TimerMode::Repeating,
+    )));
+}
+
+fn apply_difficulty(
+    difficulty: Res<Difficulty>,
+    mut timer_query: Query<&mut PipeTimer>,
+    mut velocity_query: Query<&mut PipeVelocity>,
+) {
+    if !difficulty.is_changed() {
+        return;
+    }
+
+    for mut timer in timer_query.iter_mut() {
+        timer
+            .0
+            .set_duration(Duration::from_secs_f32(difficulty.spawn_interval));
+    }
+
+    for mut velocity in velocity_query.iter_mut() {
+        velocity.0.x = -difficulty.pipe_speed;
+    }
+}
+
+fn reseed(mut rng: ResMut<PipeRng>) {
+    rng.restart();
 }
 
 fn despawn(
@@ -53,71 +281,48 @@ fn despawn(
 fn update_timer(
     mut commands: Commands,
     mut timer_query: Query<&mut PipeTimer>,
-    asset_server: Res<AssetServer>,
-    time: Res<Time>,
+    texture: Res<PipeTexture>,
+    mut rng: ResMut<PipeRng>,
+    rules: Res<GameRules>,
+    difficulty: Res<Difficulty>,
+    time: Res<FixedTime>,
 ) {
-    const INITIAL_OFFSET: f32 = 300.;
-    const GAP: f32 = 60.;
-    const OPENING: f32 = 0.;
+    let spawn_offset = rules.pipe.spawn_offset;
+    let gap = difficulty.gap;
 
     for mut timer in timer_query.iter_mut() {
-        timer.0.tick(time.delta());
+        timer.0.tick(time.period);
 
         if timer.0.just_finished() {
-            commands.spawn((
-                Pipe,
-                PipeVelocity(Vec2::new(-SPEED, 0.0)),
-                SpriteBundle {
-                    transform: Transform {
-                        translation: Vec3::new(
-                            INITIAL_OFFSET,
-                            (-PIPE_SIZE.y / 2.) - GAP + OPENING,
-                            0f32,
-                        ),
-                        ..Default::default()
-                    },
-                    texture: asset_server.load("pipe-green.png"),
-                    ..Default::default()
-                },
-            ));
-
-            commands.spawn((
-                Pipe,
-                PipeVelocity(Vec2::new(-SPEED, 0.0)),
-                SpriteBundle {
-                    transform: Transform {
-                        translation: Vec3::new(
-                            INITIAL_OFFSET,
-                            (PIPE_SIZE.y / 2.) + GAP + OPENING,
-                            0f32,
-                        ),
-                        ..Default::default()
-                    },
-                    sprite: Sprite {
-                        flip_y: true,
-                        ..Default::default()
-                    },
-                    texture: asset_server.load("pipe-green.png"),
-                    ..Default::default()
-                },
-            ));
+            let opening = rng.next_opening(gap);
+            Pipe::spawn_pair(
+                &mut commands,
+                &texture,
+                Vec2::new(spawn_offset, opening),
+                gap,
+                difficulty.pipe_speed,
+            );
         }
     }
 }
 
+fn remember_position(mut query: Query<&mut Pipe>) {
+    for mut pipe in query.iter_mut() {
+        pipe.previous = pipe.position;
+    }
+}
+
 fn update_position(
     mut commands: Commands,
-    mut query: Query<(Entity, &PipeVelocity, &mut Transform)>,
-    time: Res<Time>,
+    mut query: Query<(Entity, &PipeVelocity, &mut Pipe)>,
+    time: Res<FixedTime>,
     mut writer: EventWriter<ScoreEvent>,
 ) {
     let background_rect = Rect::from_center_size(Vec2::ZERO, BACKGROUND_SIZE);
-    for (entity, velocity, mut transform) in query.iter_mut() {
-        let old_translation = transform.translation;
-        let old_rect = Rect::from_center_size(old_translation.truncate(), Pipe::SIZE);
-        let new_translation = old_translation + velocity.0.extend(0.) * time.delta_seconds();
-        let new_rect = Rect::from_center_size(new_translation.truncate(), Pipe::SIZE);
-        transform.translation = new_translation;
+    for (entity, velocity, mut pipe) in query.iter_mut() {
+        let old_rect = Rect::from_center_size(pipe.position, Pipe::SIZE);
+        pipe.position += velocity.0 * time.period.as_secs_f32();
+        let new_rect = Rect::from_center_size(pipe.position, Pipe::SIZE);
 
         if old_rect.center().x >= Bird::X && new_rect.center().x <= Bird::X {
             writer.send(ScoreEvent);
@@ -128,3 +333,14 @@ fn update_position(
         }
     }
 }
+
+/// Places the sprite between the last two fixed ticks, so that the pipes move
+/// as smoothly as the bird and the scenery.
+fn interpolate(mut query: Query<(&Pipe, &mut Transform)>, time: Res<FixedTime>) {
+    let alpha = time.accumulated().as_secs_f32() / time.period.as_secs_f32();
+
+    for (pipe, mut transform) in query.iter_mut() {
+        let position = pipe.previous.lerp(pipe.position, alpha);
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
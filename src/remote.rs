@@ -0,0 +1,319 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    str::FromStr,
+};
+#[cfg(unix)]
+use std::{
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    bird::{Bird, BirdEvent, PendingJump, Player, PlayerInput},
+    difficulty::Difficulty,
+    env::Observation,
+    pipe::{Gap, Pipe},
+    rules::GameRules,
+    score::ScoreRes,
+    GameState, SimulationTick, TickSet,
+};
+
+pub struct RemotePlugin;
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_set(Update, PlayerInput.run_if(not(controlled)))
+            .add_systems(
+                FixedUpdate,
+                update
+                    .in_set(TickSet::Input)
+                    .run_if(resource_exists::<RemoteControl>()),
+            );
+    }
+}
+
+/// Where to listen for a controller: `tcp:<address>`, a bare `<address>` or
+/// port on the loopback interface, or `unix:<path>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteAddr {
+    Tcp(SocketAddr),
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl fmt::Display for RemoteAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteAddr::Tcp(addr) => write!(f, "tcp:{}", addr),
+            #[cfg(unix)]
+            RemoteAddr::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRemoteAddrError(String);
+
+impl fmt::Display for ParseRemoteAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid remote address {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseRemoteAddrError {}
+
+impl FromStr for RemoteAddr {
+    type Err = ParseRemoteAddrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        #[cfg(unix)]
+        if let Some(path) = s.strip_prefix("unix:") {
+            return Ok(RemoteAddr::Unix(path.into()));
+        }
+
+        let addr = s.strip_prefix("tcp:").unwrap_or(s);
+        if let Ok(port) = addr.parse::<u16>() {
+            return Ok(RemoteAddr::Tcp(SocketAddr::from(([127, 0, 0, 1], port))));
+        }
+
+        addr.parse()
+            .map(RemoteAddr::Tcp)
+            .map_err(|_| ParseRemoteAddrError(s.to_string()))
+    }
+}
+
+/// Lets a controller outside the game drive the bird over a local socket.
+///
+/// Every fixed tick the game writes its state to the controller as one line of
+/// JSON and then waits for one command line back: `flap`, `noop`, or `reset`
+/// to start a new run. The game therefore runs in lockstep with the
+/// controller, and waits for a new one whenever the current one disconnects.
+#[derive(Resource)]
+pub struct RemoteControl {
+    addr: RemoteAddr,
+    listener: Listener,
+    connection: Option<Connection>,
+}
+
+impl RemoteControl {
+    /// Listens on `addr`, which must be a loopback address for TCP.
+    pub fn bind(mut addr: RemoteAddr) -> io::Result<Self> {
+        let listener = match &addr {
+            RemoteAddr::Tcp(tcp) => {
+                if !tcp.ip().is_loopback() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "remote control only listens on loopback addresses",
+                    ));
+                }
+                Listener::Tcp(TcpListener::bind(tcp)?)
+            }
+            #[cfg(unix)]
+            RemoteAddr::Unix(path) => {
+                // A socket left behind by an earlier game would make binding fail.
+                if std::fs::metadata(path).is_ok_and(|meta| meta.file_type().is_socket()) {
+                    std::fs::remove_file(path)?;
+                }
+                Listener::Unix(UnixListener::bind(path)?)
+            }
+        };
+
+        // Port 0 picks a free port, which controllers need to know.
+        if let Listener::Tcp(tcp) = &listener {
+            addr = RemoteAddr::Tcp(tcp.local_addr()?);
+        }
+
+        Ok(RemoteControl {
+            addr,
+            listener,
+            connection: None,
+        })
+    }
+
+    pub fn addr(&self) -> &RemoteAddr {
+        &self.addr
+    }
+
+    /// Sends `frame` to the controller and waits for its command, first
+    /// waiting for a controller to connect if there is none.
+    fn exchange(&mut self, frame: &Frame) -> io::Result<Command> {
+        let connection = match &mut self.connection {
+            Some(connection) => connection,
+            None => {
+                info!("waiting for a controller on {}", self.addr);
+                let stream = self.listener.accept()?;
+                info!("controller connected");
+                self.connection.insert(Connection {
+                    reader: BufReader::new(stream.try_clone()?),
+                    writer: stream,
+                })
+            }
+        };
+
+        serde_json::to_writer(&mut connection.writer, frame)?;
+        connection.writer.write_all(b"\n")?;
+        connection.writer.flush()?;
+
+        let mut line = String::new();
+        if connection.reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        line.parse().or_else(|_| {
+            warn!("unknown controller command {:?}", line.trim());
+            Ok(Command::Noop)
+        })
+    }
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl Listener {
+    fn accept(&self) -> io::Result<Stream> {
+        match self {
+            Listener::Tcp(listener) => {
+                let (stream, _) = listener.accept()?;
+                // Every tick is one small write followed by a read.
+                stream.set_nodelay(true)?;
+                Ok(Stream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener) => Ok(Stream::Unix(listener.accept()?.0)),
+        }
+    }
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn try_clone(&self) -> io::Result<Stream> {
+        match self {
+            Stream::Tcp(stream) => stream.try_clone().map(Stream::Tcp),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.try_clone().map(Stream::Unix),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+struct Connection {
+    reader: BufReader<Stream>,
+    writer: Stream,
+}
+
+/// One line of game state sent to the controller.
+#[derive(Serialize)]
+struct Frame {
+    tick: u64,
+    state: GameState,
+    score: u32,
+    #[serde(flatten)]
+    observation: Observation,
+}
+
+enum Command {
+    Flap,
+    Noop,
+    Reset,
+}
+
+impl FromStr for Command {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "flap" => Ok(Command::Flap),
+            "noop" => Ok(Command::Noop),
+            "reset" => Ok(Command::Reset),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Run condition that is true while an external controller drives the bird.
+pub fn controlled(remote: Option<Res<RemoteControl>>) -> bool {
+    remote.is_some()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update(
+    mut remote: ResMut<RemoteControl>,
+    state: Res<State<GameState>>,
+    tick: Res<SimulationTick>,
+    score: Res<ScoreRes>,
+    bird_query: Query<(&Bird, &Player)>,
+    pipe_query: Query<(Entity, &Pipe)>,
+    rules: Res<GameRules>,
+    difficulty: Res<Difficulty>,
+    mut pending: ResMut<PendingJump>,
+    mut writer: EventWriter<BirdEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let gaps = Gap::upcoming(
+        pipe_query
+            .iter()
+            .map(|(entity, pipe)| (entity, pipe.translation())),
+    );
+    let frame = Frame {
+        tick: tick.0,
+        state: *state.get(),
+        score: score.0,
+        observation: Observation::new(Player::ONE.bird(&bird_query), &gaps, &rules, &difficulty),
+    };
+
+    match remote.exchange(&frame) {
+        Ok(Command::Flap) => {
+            pending.set(Player::ONE);
+            writer.send(BirdEvent::Jump(Player::ONE));
+        }
+        Ok(Command::Noop) => {}
+        Ok(Command::Reset) => next_state.set(GameState::Starting),
+        Err(error) => {
+            warn!("controller disconnected: {}", error);
+            remote.connection = None;
+        }
+    }
+}
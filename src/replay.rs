@@ -0,0 +1,161 @@
+use std::{fs, io, path::Path, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{BirdEvent, PendingJump, Player, PlayerInput},
+    in_run,
+    pipe::PipeRng,
+    score::ScoreRes,
+    GameState, SimulationTick, TickSet,
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_set(Update, PlayerInput.run_if(not(playing_back)))
+            .add_systems(
+                Startup,
+                seed_playback.run_if(resource_exists::<ReplayMode>()),
+            )
+            .add_systems(
+                OnEnter(GameState::Starting),
+                restart.run_if(resource_exists::<ReplayMode>()),
+            )
+            .add_systems(
+                FixedUpdate,
+                update
+                    .in_set(TickSet::Input)
+                    .run_if(in_run)
+                    .run_if(resource_exists::<ReplayMode>()),
+            )
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                finish.run_if(resource_exists::<ReplayMode>()),
+            )
+            .add_systems(
+                OnEnter(GameState::LevelComplete),
+                finish.run_if(resource_exists::<ReplayMode>()),
+            );
+    }
+}
+
+/// A recorded run: the pipe seed, the fixed tick of every jump, and the outcome.
+///
+/// Because the simulation only advances in fixed ticks, feeding the same jumps
+/// back on the same ticks with the same seed ends the run on the same tick with
+/// the same score.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub jumps: Vec<u64>,
+    pub score: u32,
+    pub end: u64,
+}
+
+impl Replay {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Replay> {
+        ron::from_str(&fs::read_to_string(path)?)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        fs::write(path, text)
+    }
+}
+
+#[derive(Resource)]
+pub enum ReplayMode {
+    /// Records every run and writes it to `path` on game over, replacing the
+    /// previous run.
+    Record { path: PathBuf, replay: Replay },
+    /// Feeds the recorded jumps to the bird in place of live input.
+    Playback { replay: Replay, cursor: usize },
+}
+
+impl ReplayMode {
+    pub fn record(path: impl Into<PathBuf>) -> Self {
+        ReplayMode::Record {
+            path: path.into(),
+            replay: Replay::default(),
+        }
+    }
+
+    pub fn playback(replay: Replay) -> Self {
+        ReplayMode::Playback { replay, cursor: 0 }
+    }
+}
+
+/// Run condition that is true while a replay drives the bird.
+pub fn playing_back(mode: Option<Res<ReplayMode>>) -> bool {
+    matches!(mode.as_deref(), Some(ReplayMode::Playback { .. }))
+}
+
+fn seed_playback(mut commands: Commands, mode: Res<ReplayMode>) {
+    if let ReplayMode::Playback { replay, .. } = mode.as_ref() {
+        commands.insert_resource(PipeRng::from_seed(replay.seed));
+    }
+}
+
+fn restart(mut mode: ResMut<ReplayMode>) {
+    match mode.as_mut() {
+        ReplayMode::Record { replay, .. } => *replay = Replay::default(),
+        ReplayMode::Playback { cursor, .. } => *cursor = 0,
+    }
+}
+
+fn update(
+    mut mode: ResMut<ReplayMode>,
+    mut pending: ResMut<PendingJump>,
+    mut writer: EventWriter<BirdEvent>,
+    tick: Res<SimulationTick>,
+) {
+    match mode.as_mut() {
+        ReplayMode::Record { replay, .. } => {
+            if pending.is_set(Player::ONE) {
+                replay.jumps.push(tick.0);
+            }
+        }
+        ReplayMode::Playback { replay, cursor } => {
+            while replay.jumps.get(*cursor) == Some(&tick.0) {
+                *cursor += 1;
+                pending.set(Player::ONE);
+                writer.send(BirdEvent::Jump(Player::ONE));
+            }
+        }
+    }
+}
+
+fn finish(
+    mut mode: ResMut<ReplayMode>,
+    score: Res<ScoreRes>,
+    rng: Res<PipeRng>,
+    tick: Res<SimulationTick>,
+) {
+    match mode.as_mut() {
+        ReplayMode::Record { path, replay } => {
+            replay.seed = rng.seed();
+            replay.score = score.0;
+            replay.end = tick.0;
+
+            match replay.save(&path) {
+                Ok(()) => info!("replay saved to {}", path.display()),
+                Err(error) => error!("failed to save replay to {}: {}", path.display(), error),
+            }
+        }
+        ReplayMode::Playback { replay, .. } => {
+            if replay.score == score.0 && replay.end == tick.0 {
+                info!("replay finished on tick {} with score {}", tick.0, score.0);
+            } else {
+                warn!(
+                    "replay diverged: expected score {} on tick {}, got score {} on tick {}",
+                    replay.score, replay.end, score.0, tick.0
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{collision::CollisionRules, difficulty::DifficultyRules};
+
+pub struct RulesPlugin;
+
+impl Plugin for RulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameRules>();
+
+        // Without an asset server the game runs on the rules built into it.
+        if app.world.contains_resource::<AssetServer>() {
+            app.add_asset::<GameRules>()
+                .init_asset_loader::<GameRulesLoader>()
+                .add_systems(Startup, init)
+                .add_systems(PreUpdate, apply);
+        }
+    }
+}
+
+/// Gameplay tuning, loaded from `assets/game.rules.ron`.
+///
+/// The file is watched, so edits are picked up by the running game. It is also
+/// built into the game as the default rules, for running without an asset
+/// server, so every value lives in the file alone.
+#[derive(Resource, Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "6f0a4c1e-93a5-4c73-a1b2-3d6f0c2b8e41"]
+pub struct GameRules {
+    pub bird: BirdRules,
+    pub pipe: PipeRules,
+    pub base: BaseRules,
+    pub difficulty: DifficultyRules,
+    pub collision: CollisionRules,
+}
+
+impl GameRules {
+    /// The rules file as it was when the game was built.
+    pub const BUILT_IN: &'static str = include_str!("../assets/game.rules.ron");
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        ron::from_str(GameRules::BUILT_IN).expect("assets/game.rules.ron should be valid rules")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BirdRules {
+    /// Downward acceleration, in units per second squared.
+    pub gravity: f32,
+    /// Upward velocity set by a jump, in units per second.
+    pub jump_velocity: f32,
+    /// Nose-up angle set by a jump, in degrees.
+    pub jump_rotation: f32,
+    /// Rate at which the nose drops, in degrees per second.
+    pub rotation_speed: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PipeRules {
+    /// Scroll speed, in units per second.
+    pub speed: f32,
+    /// Distance from the gap center to each pipe end.
+    pub gap: f32,
+    /// Horizontal position new pipes spawn at.
+    pub spawn_offset: f32,
+    /// Seconds between two pipe pairs.
+    pub spawn_interval: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BaseRules {
+    /// Scroll speed on the start screen, in units per second.
+    pub starting_speed: f32,
+    /// Scroll speed while playing, in units per second.
+    pub playing_speed: f32,
+}
+
+#[derive(Default)]
+struct GameRulesLoader;
+
+impl AssetLoader for GameRulesLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let rules = ron::de::from_bytes::<GameRules>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(rules));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["rules.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct GameRulesHandle(Handle<GameRules>);
+
+fn init(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(GameRulesHandle(asset_server.load("game.rules.ron")));
+}
+
+fn apply(
+    mut reader: EventReader<AssetEvent<GameRules>>,
+    handle: Res<GameRulesHandle>,
+    assets: Res<Assets<GameRules>>,
+    mut rules: ResMut<GameRules>,
+) {
+    for event in reader.iter() {
+        match event {
+            AssetEvent::Created { handle: changed } | AssetEvent::Modified { handle: changed }
+                if *changed == handle.0 =>
+            {
+                if let Some(loaded) = assets.get(&handle.0) {
+                    info!("game rules loaded");
+                    *rules = loaded.clone();
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,6 +1,6 @@
-use bevy::{audio::VolumeLevel, prelude::*};
+use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{bird::PlayerRound, settings::Settings, GameState, TickSet};
 
 pub struct ScorePlugin;
 
@@ -9,21 +9,63 @@ impl Plugin for ScorePlugin {
         app.init_resource::<ScoreRes>()
             .init_resource::<ScoreSound>()
             .add_event::<ScoreEvent>()
-            .add_systems(Startup, init)
+            .add_systems(Startup, init.run_if(resource_exists::<AssetServer>()))
             .add_systems(OnEnter(GameState::Starting), reset)
-            .add_systems(Update, on_event);
+            .add_systems(FixedUpdate, on_event.in_set(TickSet::Resolve))
+            .add_systems(Update, play_sound.run_if(resource_exists::<AssetServer>()));
     }
 }
 
 #[derive(Resource, Default)]
 pub struct ScoreSound(Handle<AudioSource>);
 
+/// Pipes passed this round by the bird that got the furthest.
 #[derive(Resource, Default)]
 pub struct ScoreRes(pub u32);
 
 #[derive(Event)]
 pub struct ScoreEvent;
 
+/// Awarded at the end of a round for passing enough pipes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl Medal {
+    pub const ALL: [Medal; 4] = [Medal::Bronze, Medal::Silver, Medal::Gold, Medal::Platinum];
+
+    /// Pipes to pass for the medal.
+    pub fn threshold(self) -> u32 {
+        match self {
+            Medal::Bronze => 10,
+            Medal::Silver => 20,
+            Medal::Gold => 30,
+            Medal::Platinum => 40,
+        }
+    }
+
+    /// The best medal `score` earns, if any.
+    pub fn for_score(score: u32) -> Option<Medal> {
+        Medal::ALL
+            .into_iter()
+            .rev()
+            .find(|medal| score >= medal.threshold())
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Medal::Bronze => "Bronze",
+            Medal::Silver => "Silver",
+            Medal::Gold => "Gold",
+            Medal::Platinum => "Platinum",
+        }
+    }
+}
+
 fn init(asset_server: Res<AssetServer>, mut score_sound: ResMut<ScoreSound>) {
     let sound = asset_server.load("point.ogg");
     score_sound.0 = sound;
@@ -33,23 +75,40 @@ fn reset(mut score: ResMut<ScoreRes>) {
     score.0 = 0;
 }
 
+/// Counts a pipe for the round and for every bird still in it.
 fn on_event(
-    mut commands: Commands,
     mut score: ResMut<ScoreRes>,
     mut reader: EventReader<ScoreEvent>,
-    score_sound: Res<ScoreSound>,
+    mut round_query: Query<&mut PlayerRound>,
 ) {
     if !reader.is_empty() {
         reader.clear();
         score.0 += 1;
 
+        for mut round in round_query.iter_mut() {
+            if round.alive() {
+                round.score += 1;
+            }
+        }
+    }
+}
+
+fn play_sound(
+    mut commands: Commands,
+    mut reader: EventReader<ScoreEvent>,
+    score_sound: Res<ScoreSound>,
+    settings: Option<Res<Settings>>,
+) {
+    if !reader.is_empty() {
+        reader.clear();
+
+        // The point sound sits below the flaps.
+        let settings = settings
+            .map(|settings| settings.clone())
+            .unwrap_or_default();
         commands.spawn(AudioBundle {
             source: score_sound.0.clone(),
-            settings: PlaybackSettings {
-                volume: bevy::audio::Volume::Absolute(VolumeLevel::new(0.5)),
-                ..default()
-            },
-            ..default()
+            settings: PlaybackSettings::ONCE.with_volume(settings.sfx(0.5)),
         });
     }
 }
@@ -0,0 +1,217 @@
+use bevy::{
+    audio::{AudioSinkPlayback, Volume},
+    prelude::*,
+    window::PrimaryWindow,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    action::Bindings,
+    storage,
+    theme::{DayCycle, ThemeChoice, Themes},
+    GameState,
+};
+
+const FILE: &str = "settings.ron";
+
+/// Keeps the player's settings in the data directory and applies them to the
+/// window and the music.
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load())
+            .add_systems(OnExit(GameState::Settings), save)
+            .add_systems(OnExit(GameState::Controls), save)
+            .add_systems(
+                Update,
+                (
+                    apply_window_scale,
+                    apply_music_volume.run_if(resource_changed::<Settings>()),
+                ),
+            );
+    }
+}
+
+/// Options the player can change from the settings screen.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub music_volume: f32,
+    pub muted: bool,
+    /// Size of the window relative to its default size.
+    pub window_scale: f32,
+    pub show_fps: bool,
+    /// Theme of the runs, applied as the next one starts.
+    pub theme: ThemeChoice,
+    pub day_cycle: DayCycle,
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            master_volume: 1.0,
+            sfx_volume: 1.0,
+            music_volume: 1.0,
+            muted: false,
+            window_scale: 1.0,
+            show_fps: false,
+            theme: ThemeChoice::default(),
+            day_cycle: DayCycle::default(),
+            bindings: Bindings::default(),
+        }
+    }
+}
+
+/// One line of the settings screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Setting {
+    MasterVolume,
+    SfxVolume,
+    MusicVolume,
+    Mute,
+    WindowScale,
+    ShowFps,
+    Theme,
+    DayCycle,
+}
+
+impl Setting {
+    pub const ALL: [Setting; 8] = [
+        Setting::MasterVolume,
+        Setting::SfxVolume,
+        Setting::MusicVolume,
+        Setting::Mute,
+        Setting::WindowScale,
+        Setting::ShowFps,
+        Setting::Theme,
+        Setting::DayCycle,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Setting::MasterVolume => "Master",
+            Setting::SfxVolume => "Effects",
+            Setting::MusicVolume => "Music",
+            Setting::Mute => "Mute",
+            Setting::WindowScale => "Scale",
+            Setting::ShowFps => "Show FPS",
+            Setting::Theme => "Theme",
+            Setting::DayCycle => "Cycle",
+        }
+    }
+}
+
+impl Settings {
+    const VOLUME_STEP: f32 = 0.1;
+    const SCALE_STEP: f32 = 0.5;
+    const MAX_SCALE: f32 = 3.0;
+
+    /// Reads the settings from the data directory, falling back to the
+    /// defaults if there are none or they can't be read.
+    pub fn load() -> Self {
+        match storage::load(FILE) {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(error) => {
+                warn!("failed to load settings: {}", error);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = storage::save(FILE, self) {
+            warn!("failed to save settings: {}", error);
+        }
+    }
+
+    /// Moves `setting` by `steps` steps, flipping it for an odd number of
+    /// steps if it is a switch. The theme steps through the loaded `themes`.
+    pub fn adjust(&mut self, setting: Setting, steps: i32, themes: &Themes) {
+        let volume = |value: f32| {
+            let value = value + steps as f32 * Settings::VOLUME_STEP;
+            // Rounded so that stepping back and forth lands on the same values.
+            (value.clamp(0.0, 1.0) * 10.0).round() / 10.0
+        };
+
+        match setting {
+            Setting::MasterVolume => self.master_volume = volume(self.master_volume),
+            Setting::SfxVolume => self.sfx_volume = volume(self.sfx_volume),
+            Setting::MusicVolume => self.music_volume = volume(self.music_volume),
+            Setting::Mute => self.muted ^= steps % 2 != 0,
+            Setting::WindowScale => {
+                self.window_scale = (self.window_scale + steps as f32 * Settings::SCALE_STEP)
+                    .clamp(1.0, Settings::MAX_SCALE);
+            }
+            Setting::ShowFps => self.show_fps ^= steps % 2 != 0,
+            Setting::Theme => self.theme = self.theme.step(themes, steps),
+            Setting::DayCycle => self.day_cycle = self.day_cycle.step(steps),
+        }
+    }
+
+    /// The value of `setting` as shown on the settings screen.
+    pub fn describe(&self, setting: Setting) -> String {
+        let switch = |on: bool| if on { "On" } else { "Off" }.to_string();
+
+        match setting {
+            Setting::MasterVolume => format!("{:.0}%", self.master_volume * 100.0),
+            Setting::SfxVolume => format!("{:.0}%", self.sfx_volume * 100.0),
+            Setting::MusicVolume => format!("{:.0}%", self.music_volume * 100.0),
+            Setting::Mute => switch(self.muted),
+            Setting::WindowScale => format!("{}x", self.window_scale),
+            Setting::ShowFps => switch(self.show_fps),
+            Setting::Theme => self.theme.to_string(),
+            Setting::DayCycle => self.day_cycle.to_string(),
+        }
+    }
+
+    fn level(&self, volume: f32) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master_volume * volume
+        }
+    }
+
+    /// How loud a sound effect plays, scaled by `mix` for sounds that sit
+    /// below the others.
+    pub fn sfx(&self, mix: f32) -> Volume {
+        Volume::new_absolute(self.level(self.sfx_volume) * mix)
+    }
+
+    pub fn music(&self) -> Volume {
+        Volume::new_absolute(self.level(self.music_volume))
+    }
+}
+
+/// Marks a sound as music, whose volume follows the music setting while it
+/// plays.
+#[derive(Component)]
+pub struct Music;
+
+fn save(settings: Res<Settings>) {
+    settings.save();
+}
+
+fn apply_window_scale(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    // Checked every frame, as the window only learns the scale factor of its
+    // monitor once it has been created.
+    for mut window in window_query.iter_mut() {
+        let scale = window.resolution.base_scale_factor() * f64::from(settings.window_scale);
+        if window.resolution.scale_factor_override() != Some(scale) {
+            window.resolution.set_scale_factor_override(Some(scale));
+        }
+    }
+}
+
+fn apply_music_volume(settings: Res<Settings>, sink_query: Query<&AudioSink, With<Music>>) {
+    for sink in sink_query.iter() {
+        sink.set_volume(settings.level(settings.music_volume));
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{BirdEvent, PlayerRound},
+    collision::CollisionKind,
+    highscore::GameMode,
+    level::LevelRunner,
+    played_by_hand,
+    rules::GameRules,
+    score::{ScoreEvent, ScoreRes},
+    storage, GameState, TickSet, TIMESTEP,
+};
+
+const FILE: &str = "stats.ron";
+
+/// Keeps lifetime statistics of the games played on this machine in the data
+/// directory, so that tuning changes can be checked against real play. The
+/// game has no player profiles, so everyone playing on the machine shares one
+/// set of statistics. Only rounds played by hand count.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LifetimeStats::load())
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                finish_round.run_if(played_by_hand),
+            )
+            .add_systems(
+                OnEnter(GameState::LevelComplete),
+                finish_round.run_if(played_by_hand),
+            )
+            .add_systems(
+                FixedUpdate,
+                (count_play_time, count_pipes)
+                    .in_set(TickSet::Resolve)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(played_by_hand),
+            )
+            .add_systems(Update, count_flaps.run_if(played_by_hand));
+    }
+}
+
+/// Totals over every game played on this machine.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub games: u32,
+    pub flaps: u64,
+    pub pipes: u64,
+    pub best_score: u32,
+    /// Sum of the scores of every game, for the average.
+    pub total_score: u64,
+    /// Time spent flying, not counting the start screen or pauses.
+    pub play_seconds: f64,
+    /// Birds that crashed, by what they crashed into.
+    pub deaths: HashMap<CollisionKind, u32>,
+    /// The most recent games, oldest first.
+    pub history: VecDeque<RunRecord>,
+}
+
+/// One game in the history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub score: u32,
+    /// Day of the run, as `YYYY-MM-DD`.
+    pub date: String,
+    pub mode: GameMode,
+}
+
+impl LifetimeStats {
+    /// Games kept in the history.
+    pub const HISTORY: usize = 50;
+
+    /// Reads the statistics from the data directory, starting afresh if there
+    /// are none or they can't be read.
+    pub fn load() -> Self {
+        match storage::load(FILE) {
+            Ok(stats) => stats.unwrap_or_default(),
+            Err(error) => {
+                warn!("failed to load statistics: {}", error);
+                LifetimeStats::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = storage::save(FILE, self) {
+            warn!("failed to save statistics: {}", error);
+        }
+    }
+
+    pub fn average_score(&self) -> f32 {
+        if self.games == 0 {
+            0.
+        } else {
+            self.total_score as f32 / self.games as f32
+        }
+    }
+
+    /// Adds a finished game to the totals and the history.
+    pub fn record(&mut self, run: RunRecord) {
+        self.games += 1;
+        self.best_score = self.best_score.max(run.score);
+        self.total_score += u64::from(run.score);
+
+        self.history.push_back(run);
+        while self.history.len() > LifetimeStats::HISTORY {
+            self.history.pop_front();
+        }
+    }
+}
+
+fn count_flaps(mut reader: EventReader<BirdEvent>, mut stats: ResMut<LifetimeStats>) {
+    let flaps = reader.iter().count() as u64;
+    if flaps > 0 {
+        stats.flaps += flaps;
+    }
+}
+
+/// Counts a pipe on the tick the birds pass it, like the score does, as both
+/// pipes of a pair report it.
+fn count_pipes(mut reader: EventReader<ScoreEvent>, mut stats: ResMut<LifetimeStats>) {
+    if !reader.is_empty() {
+        reader.clear();
+        stats.pipes += 1;
+    }
+}
+
+fn count_play_time(mut stats: ResMut<LifetimeStats>) {
+    stats.play_seconds += f64::from(TIMESTEP);
+}
+
+/// Records the game that just ended, and saves the statistics.
+fn finish_round(
+    mut stats: ResMut<LifetimeStats>,
+    round_query: Query<&PlayerRound>,
+    score: Res<ScoreRes>,
+    rules: Res<GameRules>,
+    level: Option<Res<LevelRunner>>,
+) {
+    for cause in round_query.iter().filter_map(|round| round.cause) {
+        *stats.deaths.entry(cause).or_default() += 1;
+    }
+
+    stats.record(RunRecord {
+        score: score.0,
+        date: storage::today(),
+        mode: GameMode::current(&rules, level.as_deref()),
+    });
+    stats.save();
+}
@@ -0,0 +1,64 @@
+use std::{env, fs, io, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Overrides the directory that game data is kept in, mostly for tests.
+pub const DATA_DIR_VAR: &str = "FLAPPY_BEVY_DATA_DIR";
+
+/// The per-user directory that game data outliving a run is kept in.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os(DATA_DIR_VAR) {
+        return Some(dir.into());
+    }
+
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    };
+
+    base.map(|base| base.join("flappy-bevy"))
+}
+
+fn path(name: &str) -> io::Result<PathBuf> {
+    data_dir()
+        .map(|dir| dir.join(name))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))
+}
+
+/// Reads `name` from the data directory, or `None` if it was never saved.
+pub fn load<T: DeserializeOwned>(name: &str) -> io::Result<Option<T>> {
+    let text = match fs::read_to_string(path(name)?) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    ron::from_str(&text)
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Writes `value` to `name` in the data directory. The file is replaced in one
+/// step, so a crash while saving leaves the previous version intact.
+pub fn save<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    let path = path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let text = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, text)?;
+    fs::rename(temporary, path)
+}
+
+/// Today's local date as `YYYY-MM-DD`.
+pub fn today() -> String {
+    chrono::Local::now().format("%Y-%m-%d").to_string()
+}
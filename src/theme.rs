@@ -0,0 +1,408 @@
+use std::fmt;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::BoxedFuture,
+};
+use chrono::{Local, Timelike};
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    parallax::{Layer, ParallaxScroll},
+    score::{ScoreEvent, ScoreRes},
+    settings::Settings,
+    GameState,
+};
+
+/// Draws the background layers of the theme picked in the settings, and
+/// cross-fades to another theme as the day goes by.
+///
+/// Themes are the `.theme.ron` files in `assets/themes`, so a theme pack is a
+/// folder of them dropped in there with its images.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Theme>()
+            .init_asset_loader::<ThemeLoader>()
+            .init_resource::<Themes>()
+            .init_resource::<ActiveTheme>()
+            .add_systems(Startup, init)
+            .add_systems(PreUpdate, collect)
+            .add_systems(OnEnter(GameState::Starting), pick)
+            .add_systems(
+                Update,
+                (
+                    pick_loaded.run_if(resource_changed::<Themes>()),
+                    advance_on_points.run_if(in_state(GameState::Playing)),
+                    follow_clock,
+                    Background::show.run_if(resource_changed::<ActiveTheme>()),
+                    Background::fade,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// The look of the game, loaded from a `.theme.ron` asset.
+#[derive(Debug, Clone, PartialEq, Deserialize, TypeUuid, TypePath)]
+#[uuid = "6f3a9c1e-8d24-4b7f-a0e5-2c9b7d1f4e63"]
+pub struct Theme {
+    pub name: String,
+    /// The layers of the background, from the furthest back.
+    pub layers: Vec<Layer>,
+    /// Hours of the local time of day that the clock cycle shows the theme
+    /// from and until. The range wraps around midnight when it ends before it
+    /// starts.
+    #[serde(default)]
+    pub hours: Option<(u32, u32)>,
+}
+
+impl Theme {
+    fn shown_at(&self, hour: u32) -> bool {
+        match self.hours {
+            Some((from, until)) if from <= until => (from..until).contains(&hour),
+            Some((from, until)) => hour >= from || hour < until,
+            None => false,
+        }
+    }
+}
+
+/// Every theme that has loaded, in the order of their files.
+#[derive(Resource, Debug, Default)]
+pub struct Themes(Vec<Theme>);
+
+impl Themes {
+    pub fn get(&self, name: &str) -> Option<&Theme> {
+        self.0.iter().find(|theme| theme.name == name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(|theme| theme.name.as_str())
+    }
+
+    /// The theme after `name`, starting over after the last one.
+    fn next(&self, name: &str) -> Option<&Theme> {
+        let index = self.0.iter().position(|theme| theme.name == name)?;
+        self.0.get((index + 1) % self.0.len())
+    }
+
+    fn at_hour(&self, hour: u32) -> Option<&Theme> {
+        self.0.iter().find(|theme| theme.shown_at(hour))
+    }
+
+    /// The theme `settings` ask for at the start of a run.
+    fn choose(&self, settings: &Settings) -> Option<&Theme> {
+        let chosen = match (settings.day_cycle, &settings.theme) {
+            (DayCycle::Clock, _) => self.at_hour(hour_of_day()),
+            (_, ThemeChoice::Named(name)) => self.get(name),
+            (_, ThemeChoice::Random) => None,
+        };
+        chosen.or_else(|| self.0.choose(&mut rand::thread_rng()))
+    }
+}
+
+/// Which theme the player wants.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeChoice {
+    /// A different theme picked at random for every run, like the original
+    /// game does.
+    #[default]
+    Random,
+    Named(String),
+}
+
+impl ThemeChoice {
+    /// The choice `steps` away among random and every theme, going around.
+    pub fn step(&self, themes: &Themes, steps: i32) -> ThemeChoice {
+        let choices: Vec<ThemeChoice> = std::iter::once(ThemeChoice::Random)
+            .chain(
+                themes
+                    .names()
+                    .map(|name| ThemeChoice::Named(name.to_string())),
+            )
+            .collect();
+        let index = choices
+            .iter()
+            .position(|choice| choice == self)
+            .unwrap_or_default();
+        let index = (index as i32 + steps).rem_euclid(choices.len() as i32);
+        choices[index as usize].clone()
+    }
+}
+
+impl fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThemeChoice::Random => write!(f, "Random"),
+            ThemeChoice::Named(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// When the theme moves on during a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DayCycle {
+    /// The theme stays for the whole run.
+    #[default]
+    Off,
+    /// Moves on to the next theme every this many points.
+    Points(u32),
+    /// Shows the theme whose hours hold the time of day.
+    Clock,
+}
+
+impl DayCycle {
+    /// The cycles the settings screen steps through.
+    const CHOICES: [DayCycle; 4] = [
+        DayCycle::Off,
+        DayCycle::Points(10),
+        DayCycle::Points(25),
+        DayCycle::Clock,
+    ];
+
+    /// The cycle `steps` away on the settings screen, going around.
+    pub fn step(self, steps: i32) -> DayCycle {
+        let choices = DayCycle::CHOICES;
+        let index = choices
+            .iter()
+            .position(|choice| *choice == self)
+            .unwrap_or_default();
+        choices[(index as i32 + steps).rem_euclid(choices.len() as i32) as usize]
+    }
+}
+
+impl fmt::Display for DayCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DayCycle::Off => write!(f, "Off"),
+            DayCycle::Points(points) => write!(f, "{} pts", points),
+            DayCycle::Clock => write!(f, "Clock"),
+        }
+    }
+}
+
+/// Name of the theme on screen, or the one being faded to.
+#[derive(Resource, Debug, Default, PartialEq, Eq)]
+pub struct ActiveTheme(pub Option<String>);
+
+#[derive(Default)]
+struct ThemeLoader;
+
+impl AssetLoader for ThemeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let theme = ron::de::from_bytes::<Theme>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(theme));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["theme.ron"]
+    }
+}
+
+/// Everything in `assets/themes`, sorted by path.
+#[derive(Resource)]
+struct ThemeFolder(Vec<HandleUntyped>);
+
+fn init(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut handles = asset_server.load_folder("themes").unwrap_or_else(|error| {
+        warn!("failed to load themes: {}", error);
+        Vec::new()
+    });
+    handles.sort_by_key(|handle| {
+        asset_server
+            .get_handle_path(handle)
+            .map(|path| path.path().to_path_buf())
+    });
+    commands.insert_resource(ThemeFolder(handles));
+}
+
+/// Rebuilds the list of themes whenever one loads or changes.
+fn collect(
+    mut reader: EventReader<AssetEvent<Theme>>,
+    folder: Res<ThemeFolder>,
+    assets: Res<Assets<Theme>>,
+    mut themes: ResMut<Themes>,
+) {
+    if reader.iter().count() == 0 {
+        return;
+    }
+
+    // The folder holds the images of the themes too.
+    themes.0 = folder
+        .0
+        .iter()
+        .filter_map(|handle| assets.get(&handle.typed_weak()))
+        .cloned()
+        .collect();
+}
+
+/// Hour of the day where the player is.
+fn hour_of_day() -> u32 {
+    Local::now().hour()
+}
+
+/// Picks the theme of the run that is starting.
+fn pick(themes: Res<Themes>, settings: Res<Settings>, mut active: ResMut<ActiveTheme>) {
+    if let Some(theme) = themes.choose(&settings) {
+        active.set_if_neq(ActiveTheme(Some(theme.name.clone())));
+    }
+}
+
+/// Picks a theme once they have loaded, or again if the one on screen is gone.
+fn pick_loaded(themes: Res<Themes>, settings: Res<Settings>, mut active: ResMut<ActiveTheme>) {
+    let shown = active.0.as_deref().and_then(|name| themes.get(name));
+    if shown.is_none() {
+        if let Some(theme) = themes.choose(&settings) {
+            active.0 = Some(theme.name.clone());
+        }
+    }
+}
+
+fn advance_on_points(
+    mut reader: EventReader<ScoreEvent>,
+    score: Res<ScoreRes>,
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    mut active: ResMut<ActiveTheme>,
+) {
+    if reader.iter().count() == 0 {
+        return;
+    }
+    let DayCycle::Points(points) = settings.day_cycle else {
+        return;
+    };
+
+    if points > 0 && score.0.is_multiple_of(points) {
+        let next = active.0.as_deref().and_then(|name| themes.next(name));
+        if let Some(theme) = next {
+            active.set_if_neq(ActiveTheme(Some(theme.name.clone())));
+        }
+    }
+}
+
+fn follow_clock(settings: Res<Settings>, themes: Res<Themes>, mut active: ResMut<ActiveTheme>) {
+    if settings.day_cycle != DayCycle::Clock {
+        return;
+    }
+
+    if let Some(theme) = themes.at_hour(hour_of_day()) {
+        active.set_if_neq(ActiveTheme(Some(theme.name.clone())));
+    }
+}
+
+/// The layers of a theme, behind everything else. While a theme fades in, its
+/// background lies on top of the one it replaces.
+#[derive(Component)]
+struct Background;
+
+#[derive(Component)]
+struct FadeIn(Timer);
+
+type BackgroundLayers<'a> = (
+    Entity,
+    &'a mut Transform,
+    Option<&'a Children>,
+    Option<&'a FadeIn>,
+);
+
+impl Background {
+    const FADE_SECONDS: f32 = 1.5;
+    const Z: f32 = -10.;
+    /// Just above the settled background.
+    const FADING_Z: f32 = -9.;
+    /// Between each layer and the next. All of them stay below `FADING_Z`.
+    const LAYER_Z: f32 = 0.01;
+
+    /// Fades in the background of the active theme. The first one appears
+    /// at once.
+    fn show(
+        mut commands: Commands,
+        mut background_query: Query<BackgroundLayers, With<Background>>,
+        mut sprite_query: Query<&mut Sprite>,
+        active: Res<ActiveTheme>,
+        themes: Res<Themes>,
+        scroll: Res<ParallaxScroll>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let Some(theme) = active.0.as_deref().and_then(|name| themes.get(name)) else {
+            return;
+        };
+
+        // A fade still going when the theme changes again finishes at once.
+        let fading = background_query.iter().any(|(.., fade)| fade.is_some());
+        for (entity, mut transform, children, fade) in background_query.iter_mut() {
+            if fade.is_some() {
+                Background::set_alpha(children, &mut sprite_query, 1.);
+                transform.translation.z = Background::Z;
+                commands.entity(entity).remove::<FadeIn>();
+            } else if fading {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        let first = background_query.is_empty();
+        let (alpha, z) = if first {
+            (1., Background::Z)
+        } else {
+            (0., Background::FADING_Z)
+        };
+        let mut background = commands.spawn((
+            Background,
+            SpatialBundle::from_transform(Transform::from_xyz(0., 0., z)),
+        ));
+        background.with_children(|parent| {
+            for (index, layer) in theme.layers.iter().enumerate() {
+                let z = index as f32 * Background::LAYER_Z;
+                let color = Color::rgba(1., 1., 1., alpha);
+                layer.spawn(parent, &asset_server, &scroll, z, color);
+            }
+        });
+        if !first {
+            background.insert(FadeIn(Timer::from_seconds(
+                Background::FADE_SECONDS,
+                TimerMode::Once,
+            )));
+        }
+    }
+
+    /// Fades the new background in, and drops the old one once it is covered.
+    fn fade(
+        mut commands: Commands,
+        mut fading_query: Query<(Entity, &mut FadeIn, &mut Transform, Option<&Children>)>,
+        settled_query: Query<Entity, (With<Background>, Without<FadeIn>)>,
+        mut sprite_query: Query<&mut Sprite>,
+        time: Res<Time>,
+    ) {
+        for (entity, mut fade, mut transform, children) in fading_query.iter_mut() {
+            fade.0.tick(time.delta());
+            Background::set_alpha(children, &mut sprite_query, fade.0.percent());
+
+            if fade.0.finished() {
+                for old in settled_query.iter() {
+                    commands.entity(old).despawn_recursive();
+                }
+                transform.translation.z = Background::Z;
+                commands.entity(entity).remove::<FadeIn>();
+            }
+        }
+    }
+
+    fn set_alpha(children: Option<&Children>, sprite_query: &mut Query<&mut Sprite>, alpha: f32) {
+        for &child in children.into_iter().flatten() {
+            if let Ok(mut sprite) = sprite_query.get_mut(child) {
+                sprite.color.set_a(alpha);
+            }
+        }
+    }
+}
@@ -1,25 +1,81 @@
-use bevy::prelude::*;
+use bevy::{
+    app::AppExit,
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
 
-use crate::{bird::BirdEvent, score::ScoreRes, GameState};
+use crate::{
+    achievement::{AchievementProgress, AchievementUnlocked, Achievements},
+    action::{self, Bindings, InputAction, Rebinding},
+    bird::{Player, PlayerCount, PlayerRound},
+    collision::CollisionKind,
+    highscore::{self, HighScores, PendingHighScore},
+    score::{Medal, ScoreRes},
+    settings::{Setting, Settings},
+    stats::LifetimeStats,
+    theme::Themes,
+    GameState,
+};
 
 const BACKGROUND_COLOR: Color = Color::rgb(234. / 255., 97. / 255., 1. / 255.);
 const BORDER_COLOR: Color = Color::rgb(251. / 255., 253. / 255., 235. / 255.);
+const HIGHLIGHT_COLOR: Color = Color::rgb(1., 214. / 255., 64. / 255.);
+const DETAIL_COLOR: Color = Color::rgb(190. / 255., 190. / 255., 180. / 255.);
+/// Longest name that fits on a high score line.
+const NAME_LENGTH: usize = 12;
 
 pub struct UiPlugin;
 
 macro_rules! screen_ui {
     ($app:ident, $bundle:ident, $state:expr) => {
         $app.add_systems(OnEnter($state), $bundle::on_enter)
-            .add_systems(Update, $bundle::update.run_if(in_state($state)))
+            .add_systems(
+                Update,
+                $bundle::update
+                    .run_if(in_state($state))
+                    .after(navigate_menus),
+            )
     };
 }
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
-        let app = app.add_systems(Update, ScoreText::update);
-        let app = screen_ui!(app, StartingUI, GameState::Starting);
+        let app = app
+            .add_systems(Startup, (FpsText::spawn, Toast::spawn_stack))
+            .add_systems(
+                Update,
+                (
+                    ScoreText::update,
+                    FpsText::update,
+                    SlideIn::update,
+                    (Toast::spawn, Toast::update).chain(),
+                    navigate_menus,
+                ),
+            );
+        let app = screen_ui!(app, StartingUI, GameState::Starting).add_systems(
+            Update,
+            StartPanel::update.run_if(in_state(GameState::Starting)),
+        );
         let app = screen_ui!(app, PlayingUI, GameState::Playing);
-        let _app = screen_ui!(app, GameOverUI, GameState::GameOver);
+        // The game over screen needs to know whether the run made the table.
+        let app = app
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                GameOverUI::on_enter.after(highscore::propose),
+            )
+            .add_systems(
+                Update,
+                (
+                    (GameOverUI::update, NameEntryUI::update).chain(),
+                    CountUp::update,
+                )
+                    .run_if(in_state(GameState::GameOver))
+                    .after(navigate_menus),
+            );
+        let app = screen_ui!(app, PausedUI, GameState::Paused);
+        let app = screen_ui!(app, SettingsUI, GameState::Settings);
+        let app = screen_ui!(app, ControlsUI, GameState::Controls);
+        let _app = screen_ui!(app, LevelCompleteUI, GameState::LevelComplete);
     }
 }
 
@@ -29,6 +85,65 @@ pub struct UiRoot;
 #[derive(Component)]
 pub struct ResetButton;
 
+impl ResetButton {
+    pub fn spawn(parent: &mut ChildBuilder, asset_server: &AssetServer) {
+        spawn_button(
+            parent,
+            asset_server,
+            "Restart",
+            72.0,
+            (
+                ResetButton,
+                RestartDelay(Timer::from_seconds(RestartDelay::SECONDS, TimerMode::Once)),
+            ),
+        );
+    }
+}
+
+/// Keeps the restart button hidden and inactive for a moment after it appears,
+/// so that a flap that came too late doesn't skip the screen.
+#[derive(Component)]
+pub struct RestartDelay(Timer);
+
+impl RestartDelay {
+    const SECONDS: f32 = 0.8;
+}
+
+/// A bordered text button tagged with `marker`.
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    asset_server: &AssetServer,
+    label: &str,
+    width: f32,
+    marker: impl Bundle,
+) {
+    parent
+        .spawn((
+            marker,
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(width),
+                    height: Val::Px(30.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    // horizontally center child text
+                    justify_content: JustifyContent::Center,
+                    // vertically center child text
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(BORDER_COLOR),
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                text_style(asset_server, 16.0, BORDER_COLOR),
+            ));
+        });
+}
+
 #[derive(Component)]
 pub struct ScoreText;
 
@@ -54,11 +169,215 @@ impl ScoreText {
 }
 
 fn despawn(commands: &mut Commands, root_query: Query<Entity, With<UiRoot>>) {
-    if let Ok(entity) = root_query.get_single() {
+    for entity in root_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
 }
 
+/// Moves the focus through the buttons on screen with the direction actions,
+/// in reading order, and presses the focused button on `Confirm`. Nothing has
+/// the focus until a direction is pressed, so mouse players never see it.
+fn navigate_menus(
+    actions: Res<Input<InputAction>>,
+    root_query: Query<Entity, With<UiRoot>>,
+    children_query: Query<&Children>,
+    mut button_query: Query<(&mut Interaction, &mut BorderColor), With<Button>>,
+    mut focus: Local<Option<Entity>>,
+    mut pressed: Local<Option<Entity>>,
+) {
+    // A press lasts for one frame, like a click.
+    if let Some((mut interaction, _)) = pressed
+        .take()
+        .and_then(|entity| button_query.get_mut(entity).ok())
+    {
+        if *interaction == Interaction::Pressed {
+            *interaction = Interaction::None;
+        }
+    }
+
+    let mut roots: Vec<_> = root_query.iter().collect();
+    roots.sort();
+    let mut buttons = Vec::new();
+    let mut stack: Vec<_> = roots.into_iter().rev().collect();
+    while let Some(entity) = stack.pop() {
+        if button_query.contains(entity) {
+            buttons.push(entity);
+        }
+        if let Ok(children) = children_query.get(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+
+    let current = focus.and_then(|focus| buttons.iter().position(|&button| button == focus));
+    let forward = actions.any_just_pressed([InputAction::Down, InputAction::Right]);
+    let backward = actions.any_just_pressed([InputAction::Up, InputAction::Left]);
+    let count = buttons.len();
+    let next = match (current, forward, backward) {
+        _ if count == 0 || forward == backward => current,
+        (Some(index), true, false) => Some((index + 1) % count),
+        (Some(index), false, true) => Some((index + count - 1) % count),
+        (None, true, false) => Some(0),
+        _ => Some(count - 1),
+    };
+    *focus = next.map(|index| buttons[index]);
+
+    for &button in &buttons {
+        let Ok((mut interaction, mut border)) = button_query.get_mut(button) else {
+            continue;
+        };
+
+        let color = if *focus == Some(button) {
+            HIGHLIGHT_COLOR
+        } else {
+            BORDER_COLOR
+        };
+        if border.0 != color {
+            border.0 = color;
+        }
+
+        if *focus == Some(button) && actions.just_pressed(InputAction::Confirm) {
+            *interaction = Interaction::Pressed;
+            *pressed = Some(button);
+        }
+    }
+}
+
+fn text_style(asset_server: &AssetServer, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: asset_server.load("Minecraft.ttf"),
+        font_size,
+        color,
+    }
+}
+
+/// A panel listing the high scores, with the entry at `highlight` picked out.
+struct HighScoreTable;
+
+impl HighScoreTable {
+    fn spawn(
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        scores: &HighScores,
+        highlight: Option<usize>,
+    ) {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(3.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(BORDER_COLOR),
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "High Scores",
+                    text_style(asset_server, 18.0, BORDER_COLOR),
+                ));
+
+                if scores.entries().is_empty() {
+                    parent.spawn(TextBundle::from_section(
+                        "No scores yet",
+                        text_style(asset_server, 12.0, DETAIL_COLOR),
+                    ));
+                }
+
+                for (rank, entry) in scores.entries().iter().enumerate() {
+                    let color = if highlight == Some(rank) {
+                        HIGHLIGHT_COLOR
+                    } else {
+                        BORDER_COLOR
+                    };
+
+                    parent.spawn(
+                        TextBundle::from_sections([
+                            TextSection::new(
+                                format!("{:>2}. {:<12} {:>4}\n", rank + 1, entry.name, entry.score),
+                                text_style(asset_server, 12.0, color),
+                            ),
+                            TextSection::new(
+                                format!("{}  {}", entry.date, entry.mode),
+                                text_style(asset_server, 8.0, DETAIL_COLOR),
+                            ),
+                        ])
+                        .with_text_alignment(TextAlignment::Center),
+                    );
+                }
+            });
+    }
+}
+
+/// How the players of a multiplayer round placed.
+struct ResultsTable;
+
+impl ResultsTable {
+    /// Ranks the players by score, and then by who stayed up the longest.
+    /// Players that can't be told apart share a place.
+    fn rank(mut rounds: Vec<(Player, PlayerRound)>) -> Vec<(usize, Player, PlayerRound)> {
+        let key = |round: &PlayerRound| {
+            (
+                std::cmp::Reverse(round.score),
+                std::cmp::Reverse(round.crashed.unwrap_or(u64::MAX)),
+            )
+        };
+        rounds.sort_by_key(|(player, round)| (key(round), *player));
+
+        let mut places: Vec<(usize, Player, PlayerRound)> = Vec::new();
+        for (index, (player, round)) in rounds.into_iter().enumerate() {
+            let place = match places.last() {
+                Some((place, _, last)) if key(last) == key(&round) => *place,
+                _ => index + 1,
+            };
+            places.push((place, player, round));
+        }
+        places
+    }
+
+    fn spawn(
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        rounds: Vec<(Player, PlayerRound)>,
+    ) {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(3.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(BORDER_COLOR),
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "Results",
+                    text_style(asset_server, 18.0, BORDER_COLOR),
+                ));
+
+                for (place, player, round) in ResultsTable::rank(rounds) {
+                    let color = if place == 1 {
+                        HIGHLIGHT_COLOR
+                    } else {
+                        player.tint()
+                    };
+                    parent.spawn(TextBundle::from_section(
+                        format!("{}. {}  {:>4}", place, player, round.score),
+                        text_style(asset_server, 14.0, color),
+                    ));
+                }
+            });
+    }
+}
+
 #[derive(Bundle)]
 struct StartingUI {
     root: UiRoot,
@@ -70,6 +389,8 @@ impl StartingUI {
         mut commands: Commands,
         root_query: Query<Entity, With<UiRoot>>,
         asset_server: Res<AssetServer>,
+        settings: Res<Settings>,
+        count: Res<PlayerCount>,
     ) {
         despawn(&mut commands, root_query);
         commands.spawn(Self {
@@ -79,25 +400,384 @@ impl StartingUI {
                 ..default()
             },
         });
+        commands
+            .spawn((
+                UiRoot,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::FlexEnd,
+                        padding: UiRect::bottom(Val::Px(8.0)),
+                        row_gap: Val::Px(4.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    PlayerCountText,
+                    TextBundle::from_section(
+                        StartingUI::player_count(*count),
+                        text_style(&asset_server, 12.0, HIGHLIGHT_COLOR),
+                    ),
+                ));
+                for panel in StartPanel::ALL {
+                    if let Some(binding) = settings.bindings.get(panel.action()).first() {
+                        parent.spawn(TextBundle::from_section(
+                            format!("{}: {}", binding, panel.hint()),
+                            text_style(&asset_server, 10.0, BORDER_COLOR),
+                        ));
+                    }
+                }
+            });
+    }
+
+    fn player_count(count: PlayerCount) -> String {
+        match count.0 {
+            1 => "< 1 player >".to_string(),
+            count => format!("< {} players >", count),
+        }
     }
 
+    /// Changes the number of players with left and right.
     pub fn update(
-        mut next_state: ResMut<NextState<GameState>>,
-        keyboard: Res<Input<KeyCode>>,
-        mouse: Res<Input<MouseButton>>,
-        mut writer: EventWriter<BirdEvent>,
+        actions: Res<Input<InputAction>>,
+        mut count_query: Query<&mut Text, With<PlayerCountText>>,
+        mut count: ResMut<PlayerCount>,
+    ) {
+        let step = i32::from(actions.just_pressed(InputAction::Right))
+            - i32::from(actions.just_pressed(InputAction::Left));
+        if step != 0 {
+            let new = PlayerCount::new(count.0.saturating_add_signed(step as isize));
+            // Only a real change respawns the birds.
+            count.set_if_neq(new);
+            for mut text in count_query.iter_mut() {
+                text.sections[0].value = StartingUI::player_count(new);
+            }
+        }
+    }
+}
+
+/// A panel that opens over the start screen. Only one is open at a time.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum StartPanel {
+    HighScores,
+    Achievements,
+    Stats,
+}
+
+impl StartPanel {
+    const ALL: [StartPanel; 3] = [
+        StartPanel::HighScores,
+        StartPanel::Achievements,
+        StartPanel::Stats,
+    ];
+
+    /// The action that opens and closes the panel.
+    fn action(self) -> InputAction {
+        match self {
+            StartPanel::HighScores => InputAction::Scores,
+            StartPanel::Achievements => InputAction::Achievements,
+            StartPanel::Stats => InputAction::Stats,
+        }
+    }
+
+    fn hint(self) -> &'static str {
+        match self {
+            StartPanel::HighScores => "high scores",
+            StartPanel::Achievements => "achievements",
+            StartPanel::Stats => "stats",
+        }
+    }
+
+    /// Opens the panel whose action was pressed in place of the open one, or
+    /// closes it if it is the open one.
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        mut commands: Commands,
+        actions: Res<Input<InputAction>>,
+        panel_query: Query<(Entity, &StartPanel)>,
+        scores: Res<HighScores>,
+        achievements: Res<Achievements>,
+        progress: Res<AchievementProgress>,
+        stats: Res<LifetimeStats>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let Some(pressed) = StartPanel::ALL
+            .into_iter()
+            .find(|panel| actions.just_pressed(panel.action()))
+        else {
+            return;
+        };
+
+        let mut was_open = false;
+        for (entity, panel) in panel_query.iter() {
+            was_open |= *panel == pressed;
+            commands.entity(entity).despawn_recursive();
+        }
+        if was_open {
+            return;
+        }
+
+        commands
+            .spawn((
+                UiRoot,
+                pressed,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| match pressed {
+                StartPanel::HighScores => {
+                    HighScoreTable::spawn(parent, &asset_server, &scores, None)
+                }
+                StartPanel::Achievements => {
+                    AchievementList::spawn(parent, &asset_server, &achievements, &progress)
+                }
+                StartPanel::Stats => StatsPanel::spawn(parent, &asset_server, &stats),
+            });
+    }
+}
+
+/// Every achievement with how far along it is. Unlocked ones stand out.
+struct AchievementList;
+
+impl AchievementList {
+    fn spawn(
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        achievements: &Achievements,
+        progress: &AchievementProgress,
+    ) {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(3.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(BORDER_COLOR),
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                let unlocked = achievements
+                    .0
+                    .iter()
+                    .filter(|achievement| progress.is_unlocked(achievement))
+                    .count();
+                parent.spawn(TextBundle::from_section(
+                    format!("Achievements {}/{}", unlocked, achievements.0.len()),
+                    text_style(asset_server, 18.0, BORDER_COLOR),
+                ));
+
+                for achievement in achievements.0.iter() {
+                    let (color, status) = if progress.is_unlocked(achievement) {
+                        (HIGHLIGHT_COLOR, "Unlocked".to_string())
+                    } else {
+                        let (current, target) = achievement.goal.progress(progress);
+                        (DETAIL_COLOR, format!("{}/{}", current.min(target), target))
+                    };
+
+                    parent.spawn(
+                        TextBundle::from_sections([
+                            TextSection::new(
+                                format!("{}\n", achievement.name),
+                                text_style(asset_server, 12.0, color),
+                            ),
+                            TextSection::new(
+                                format!("{}  {}", achievement.description, status),
+                                text_style(asset_server, 8.0, DETAIL_COLOR),
+                            ),
+                        ])
+                        .with_text_alignment(TextAlignment::Center),
+                    );
+                }
+            });
+    }
+}
+
+/// The lifetime statistics, with a chart of the scores of the recent games.
+struct StatsPanel;
+
+impl StatsPanel {
+    const CHART_SIZE: Vec2 = Vec2::new(200., 60.);
+
+    fn spawn(parent: &mut ChildBuilder, asset_server: &AssetServer, stats: &LifetimeStats) {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    row_gap: Val::Px(3.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(BORDER_COLOR),
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "Stats",
+                    text_style(asset_server, 18.0, BORDER_COLOR),
+                ));
+
+                let deaths = |kind| stats.deaths.get(&kind).copied().unwrap_or_default();
+                let rows = [
+                    ("Games", stats.games.to_string()),
+                    ("Flaps", stats.flaps.to_string()),
+                    ("Pipes", stats.pipes.to_string()),
+                    ("Best", stats.best_score.to_string()),
+                    ("Average", format!("{:.1}", stats.average_score())),
+                    ("Play time", StatsPanel::duration(stats.play_seconds)),
+                    (
+                        "Deaths",
+                        format!(
+                            "{} pipe  {} ground  {} top",
+                            deaths(CollisionKind::Pipe),
+                            deaths(CollisionKind::Ground),
+                            deaths(CollisionKind::Ceiling),
+                        ),
+                    ),
+                ];
+                for (label, value) in rows {
+                    StatsPanel::spawn_row(parent, asset_server, label, value);
+                }
+
+                parent.spawn(TextBundle::from_section(
+                    "Recent scores",
+                    text_style(asset_server, 10.0, BORDER_COLOR),
+                ));
+                StatsPanel::spawn_chart(parent, asset_server, stats);
+            });
+    }
+
+    fn spawn_row(
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        label: &str,
+        value: String,
     ) {
-        if keyboard.just_pressed(KeyCode::Space) || mouse.just_pressed(MouseButton::Left) {
-            next_state.set(GameState::Playing);
-            writer.send(BirdEvent::Jump);
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(StatsPanel::CHART_SIZE.x),
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    label,
+                    text_style(asset_server, 10.0, DETAIL_COLOR),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    value,
+                    text_style(asset_server, 10.0, BORDER_COLOR),
+                ));
+            });
+    }
+
+    /// A bar for each game in the history, scaled to the best of them, with the
+    /// latest picked out.
+    fn spawn_chart(parent: &mut ChildBuilder, asset_server: &AssetServer, stats: &LifetimeStats) {
+        let top = stats
+            .history
+            .iter()
+            .map(|run| run.score)
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let slot = StatsPanel::CHART_SIZE.x / LifetimeStats::HISTORY as f32;
+
+        parent.spawn(TextBundle::from_section(
+            top.to_string(),
+            text_style(asset_server, 8.0, DETAIL_COLOR),
+        ));
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(StatsPanel::CHART_SIZE.x),
+                    height: Val::Px(StatsPanel::CHART_SIZE.y),
+                    align_items: AlignItems::FlexEnd,
+                    border: UiRect {
+                        left: Val::Px(1.0),
+                        bottom: Val::Px(1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                border_color: BorderColor(DETAIL_COLOR),
+                ..default()
+            })
+            .with_children(|parent| {
+                let last = stats.history.len().saturating_sub(1);
+                for (index, run) in stats.history.iter().enumerate() {
+                    let color = if index == last {
+                        HIGHLIGHT_COLOR
+                    } else {
+                        BORDER_COLOR
+                    };
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(slot - 1.0),
+                            margin: UiRect::left(Val::Px(1.0)),
+                            height: Val::Percent(100.0 * run.score as f32 / top as f32),
+                            // Games without a pipe still show.
+                            min_height: Val::Px(1.0),
+                            ..default()
+                        },
+                        background_color: color.into(),
+                        ..default()
+                    });
+                }
+            });
+    }
+
+    /// `seconds` as hours and minutes, or minutes and seconds under an hour.
+    fn duration(seconds: f64) -> String {
+        let seconds = seconds as u64;
+        if seconds >= 3600 {
+            format!("{}h {:02}m", seconds / 3600, seconds / 60 % 60)
+        } else {
+            format!("{}m {:02}s", seconds / 60, seconds % 60)
         }
     }
 }
 
+#[derive(Component)]
+struct PlayerCountText;
+
 struct PlayingUI;
 
+/// One player's score in a multiplayer round.
+#[derive(Component)]
+struct PlayerScoreText(Player);
+
 impl PlayingUI {
-    pub fn spawn(mut commands: Commands, score: Res<ScoreRes>) {
+    pub fn spawn(
+        mut commands: Commands,
+        score: Res<ScoreRes>,
+        count: Res<PlayerCount>,
+        asset_server: Res<AssetServer>,
+    ) {
         commands
             .spawn((
                 UiRoot,
@@ -113,93 +793,1053 @@ impl PlayingUI {
             ))
             .with_children(|parent| {
                 parent.spawn(ScoreText::new(score));
+                if count.0 == 1 {
+                    return;
+                }
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::FlexEnd,
+                            padding: UiRect::all(Val::Px(4.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for player in count.players() {
+                            parent.spawn((
+                                PlayerScoreText(player),
+                                TextBundle::from_section(
+                                    format!("{} 0", player),
+                                    text_style(&asset_server, 12.0, player.tint()),
+                                ),
+                            ));
+                        }
+                    });
             });
     }
 
     pub fn on_enter(
         mut commands: Commands,
         score: Res<ScoreRes>,
+        count: Res<PlayerCount>,
+        asset_server: Res<AssetServer>,
         root_query: Query<Entity, With<UiRoot>>,
     ) {
         despawn(&mut commands, root_query);
-        PlayingUI::spawn(commands, score);
+        PlayingUI::spawn(commands, score, count, asset_server);
     }
 
-    pub fn update() {}
+    /// Keeps the players' scores up to date, and fades out those that crashed.
+    pub fn update(
+        mut text_query: Query<(&mut Text, &PlayerScoreText)>,
+        bird_query: Query<(&Player, &PlayerRound)>,
+    ) {
+        for (mut text, PlayerScoreText(player)) in text_query.iter_mut() {
+            let Some((_, round)) = bird_query.iter().find(|(bird, _)| *bird == player) else {
+                continue;
+            };
+            let section = &mut text.sections[0];
+            section.value = format!("{} {}", player, round.score);
+            section.style.color = player.tint().with_a(if round.alive() { 1.0 } else { 0.4 });
+        }
+    }
 }
 
-pub struct GameOverUI;
+struct PausedUI;
 
-impl GameOverUI {
-    pub fn spawn(commands: &mut Commands, asset_server: Res<AssetServer>) {
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+    Resume,
+    Restart,
+    Settings,
+    Quit,
+}
+
+impl PausedUI {
+    pub fn on_enter(
+        mut commands: Commands,
+        root_query: Query<Entity, With<UiRoot>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        despawn(&mut commands, root_query);
         commands
             .spawn((
                 UiRoot,
                 NodeBundle {
                     style: Style {
                         width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
                         align_items: AlignItems::Center,
                         justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(8.0),
                         ..default()
                     },
                     ..default()
                 },
             ))
             .with_children(|parent| {
-                parent
-                    .spawn((
-                        ResetButton,
-                        ButtonBundle {
-                            style: Style {
-                                width: Val::Px(72.0),
-                                height: Val::Px(30.0),
-                                border: UiRect::all(Val::Px(2.0)),
-                                // horizontally center child text
-                                justify_content: JustifyContent::Center,
-                                // vertically center child text
-                                align_items: AlignItems::Center,
-                                ..default()
-                            },
-                            border_color: BorderColor(BORDER_COLOR),
-                            background_color: BACKGROUND_COLOR.into(),
-                            ..default()
-                        },
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn(TextBundle::from_section(
-                            "Restart",
-                            TextStyle {
-                                font: asset_server.load("Minecraft.ttf"),
-                                font_size: 16.0,
-                                color: BORDER_COLOR,
-                            },
-                        ));
-                    });
+                parent.spawn(TextBundle::from_section(
+                    "Paused",
+                    text_style(&asset_server, 24.0, BORDER_COLOR),
+                ));
+
+                for (label, button) in [
+                    ("Resume", PauseButton::Resume),
+                    ("Restart", PauseButton::Restart),
+                    ("Settings", PauseButton::Settings),
+                    ("Quit", PauseButton::Quit),
+                ] {
+                    spawn_button(parent, &asset_server, label, 96.0, button);
+                }
             });
     }
 
+    pub fn update(
+        button_query: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+        mut next_state: ResMut<NextState<GameState>>,
+        mut exit: EventWriter<AppExit>,
+    ) {
+        for (interaction, button) in button_query.iter() {
+            if *interaction != Interaction::Pressed {
+                continue;
+            }
+
+            match button {
+                PauseButton::Resume => next_state.set(GameState::Playing),
+                PauseButton::Restart => next_state.set(GameState::Starting),
+                PauseButton::Settings => next_state.set(GameState::Settings),
+                PauseButton::Quit => exit.send(AppExit),
+            }
+        }
+    }
+}
+
+struct SettingsUI;
+
+/// Steps a setting when pressed.
+#[derive(Component)]
+struct SettingButton {
+    setting: Setting,
+    steps: i32,
+}
+
+/// The shown value of a setting.
+#[derive(Component)]
+struct SettingValue(Setting);
+
+#[derive(Component)]
+struct BackButton;
+
+#[derive(Component)]
+struct ControlsButton;
+
+impl SettingsUI {
     pub fn on_enter(
         mut commands: Commands,
         root_query: Query<Entity, With<UiRoot>>,
         asset_server: Res<AssetServer>,
+        settings: Res<Settings>,
     ) {
         despawn(&mut commands, root_query);
-        GameOverUI::spawn(&mut commands, asset_server);
+        commands
+            .spawn((
+                UiRoot,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "Settings",
+                    text_style(&asset_server, 24.0, BORDER_COLOR),
+                ));
+
+                for setting in Setting::ALL {
+                    SettingsUI::spawn_row(parent, &asset_server, &settings, setting);
+                }
+
+                spawn_button(parent, &asset_server, "Controls", 96.0, ControlsButton);
+                spawn_button(parent, &asset_server, "Back", 96.0, BackButton);
+            });
     }
 
-    pub fn update(
-        keyboard: Res<Input<KeyCode>>,
-        mut reset_query: Query<&Interaction, (Changed<Interaction>, With<ResetButton>)>,
-        mut next_state: ResMut<NextState<GameState>>,
+    /// The name of `setting` followed by its value between step buttons.
+    fn spawn_row(
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        settings: &Settings,
+        setting: Setting,
     ) {
-        if keyboard.just_pressed(KeyCode::Space) {
-            next_state.set(GameState::Starting);
-        }
+        let cell = |width: f32| NodeBundle {
+            style: Style {
+                width: Val::Px(width),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        };
 
-        for interaction in &mut reset_query {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(cell(88.0)).with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        setting.label(),
+                        text_style(asset_server, 14.0, BORDER_COLOR),
+                    ));
+                });
+                spawn_button(
+                    parent,
+                    asset_server,
+                    "<",
+                    24.0,
+                    SettingButton { setting, steps: -1 },
+                );
+                parent.spawn(cell(56.0)).with_children(|parent| {
+                    parent.spawn((
+                        SettingValue(setting),
+                        TextBundle::from_section(
+                            settings.describe(setting),
+                            text_style(asset_server, 14.0, HIGHLIGHT_COLOR),
+                        ),
+                    ));
+                });
+                spawn_button(
+                    parent,
+                    asset_server,
+                    ">",
+                    24.0,
+                    SettingButton { setting, steps: 1 },
+                );
+            });
+    }
+
+    pub fn update(
+        step_query: Query<(&Interaction, &SettingButton), Changed<Interaction>>,
+        back_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+        controls_query: Query<&Interaction, (Changed<Interaction>, With<ControlsButton>)>,
+        mut value_query: Query<(&mut Text, &SettingValue)>,
+        mut settings: ResMut<Settings>,
+        mut next_state: ResMut<NextState<GameState>>,
+        themes: Res<Themes>,
+    ) {
+        for (interaction, button) in step_query.iter() {
             if *interaction == Interaction::Pressed {
+                settings.adjust(button.setting, button.steps, &themes);
+            }
+        }
+
+        if settings.is_changed() {
+            for (mut text, value) in value_query.iter_mut() {
+                text.sections[0].value = settings.describe(value.0);
+            }
+        }
+
+        let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+        if back_query.iter().any(pressed) {
+            next_state.set(GameState::Paused);
+        }
+        if controls_query.iter().any(pressed) {
+            next_state.set(GameState::Controls);
+        }
+    }
+}
+
+struct ControlsUI;
+
+/// A row of the controls screen, which listens for a new input for its action
+/// when pressed.
+#[derive(Component)]
+struct RebindButton(InputAction);
+
+/// The inputs bound to an action.
+#[derive(Component)]
+struct BindingText(InputAction);
+
+#[derive(Component)]
+struct DefaultBindingsButton;
+
+impl ControlsUI {
+    pub fn on_enter(
+        mut commands: Commands,
+        root_query: Query<Entity, With<UiRoot>>,
+        asset_server: Res<AssetServer>,
+        settings: Res<Settings>,
+    ) {
+        despawn(&mut commands, root_query);
+        commands
+            .spawn((
+                UiRoot,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(3.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "Controls",
+                    text_style(&asset_server, 20.0, BORDER_COLOR),
+                ));
+
+                for action in InputAction::ALL {
+                    ControlsUI::spawn_row(parent, &asset_server, &settings.bindings, action);
+                }
+
+                spawn_button(
+                    parent,
+                    &asset_server,
+                    "Defaults",
+                    96.0,
+                    DefaultBindingsButton,
+                );
+                spawn_button(parent, &asset_server, "Back", 96.0, BackButton);
+            });
+    }
+
+    /// The name of `action` and what it is bound to, as a button that rebinds
+    /// it. Kept short so that every action fits on the screen.
+    fn spawn_row(
+        parent: &mut ChildBuilder,
+        asset_server: &AssetServer,
+        bindings: &Bindings,
+        action: InputAction,
+    ) {
+        parent
+            .spawn((
+                RebindButton(action),
+                ButtonBundle {
+                    style: Style {
+                        width: Val::Px(260.0),
+                        height: Val::Px(21.0),
+                        border: UiRect::all(Val::Px(1.0)),
+                        padding: UiRect::horizontal(Val::Px(4.0)),
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    border_color: BorderColor(BORDER_COLOR),
+                    background_color: BACKGROUND_COLOR.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(72.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            action.label(),
+                            text_style(asset_server, 12.0, BORDER_COLOR),
+                        ));
+                    });
+                parent.spawn((
+                    BindingText(action),
+                    TextBundle::from_section(
+                        bindings.describe(action),
+                        text_style(asset_server, 10.0, DETAIL_COLOR),
+                    ),
+                ));
+            });
+    }
+
+    /// Binds the next input pressed after a row is pressed to its action, in place
+    /// of the action's other inputs on that device.
+    #[allow(clippy::too_many_arguments)]
+    pub fn update(
+        rebind_query: Query<(&Interaction, &RebindButton), Changed<Interaction>>,
+        defaults_query: Query<&Interaction, (Changed<Interaction>, With<DefaultBindingsButton>)>,
+        back_query: Query<&Interaction, (Changed<Interaction>, With<BackButton>)>,
+        mut text_query: Query<(&mut Text, &BindingText)>,
+        mut rebinding: ResMut<Rebinding>,
+        mut settings: ResMut<Settings>,
+        keyboard: Res<Input<KeyCode>>,
+        mouse: Res<Input<MouseButton>>,
+        gamepad: Res<Input<GamepadButton>>,
+        mut next_state: ResMut<NextState<GameState>>,
+    ) {
+        let pressed = |interaction: &Interaction| *interaction == Interaction::Pressed;
+
+        // Checked before the buttons, so that the click on a row isn't taken
+        // for the input to bind.
+        if let Some(action) = rebinding.0 {
+            if let Some(binding) = action::just_pressed_binding(action, &keyboard, &mouse, &gamepad)
+            {
+                settings.bindings.rebind(action, binding);
+                rebinding.0 = None;
+            }
+        } else {
+            for (interaction, button) in rebind_query.iter() {
+                if pressed(interaction) {
+                    rebinding.0 = Some(button.0);
+                }
+            }
+            if defaults_query.iter().any(pressed) {
+                settings.bindings = Bindings::default();
+            }
+            if back_query.iter().any(pressed) {
+                next_state.set(GameState::Settings);
+            }
+        }
+
+        if settings.is_changed() || rebinding.is_changed() {
+            for (mut text, binding) in text_query.iter_mut() {
+                let section = &mut text.sections[0];
+                if rebinding.0 == Some(binding.0) {
+                    section.value = "Press any input".to_string();
+                    section.style.color = HIGHLIGHT_COLOR;
+                } else {
+                    section.value = settings.bindings.describe(binding.0);
+                    section.style.color = DETAIL_COLOR;
+                }
+            }
+        }
+    }
+}
+
+/// Frames per second in the corner of the window, when the setting is on.
+#[derive(Component)]
+struct FpsText;
+
+impl FpsText {
+    fn spawn(mut commands: Commands, asset_server: Res<AssetServer>) {
+        commands.spawn((
+            FpsText,
+            TextBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(4.0),
+                    right: Val::Px(4.0),
+                    ..default()
+                },
+                z_index: ZIndex::Global(1),
+                visibility: Visibility::Hidden,
+                ..TextBundle::from_section("", text_style(&asset_server, 10.0, BORDER_COLOR))
+            },
+        ));
+    }
+
+    fn update(
+        mut query: Query<(&mut Text, &mut Visibility), With<FpsText>>,
+        settings: Option<Res<Settings>>,
+        diagnostics: Option<Res<DiagnosticsStore>>,
+    ) {
+        let fps = diagnostics
+            .as_ref()
+            .and_then(|diagnostics| diagnostics.get(FrameTimeDiagnosticsPlugin::FPS))
+            .and_then(|fps| fps.smoothed());
+        let shown = settings.is_some_and(|settings| settings.show_fps);
+
+        for (mut text, mut visibility) in query.iter_mut() {
+            visibility.set_if_neq(if shown {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+            if let (true, Some(fps)) = (shown, fps) {
+                text.sections[0].value = format!("{:.0} FPS", fps);
+            }
+        }
+    }
+}
+
+/// A note that an achievement was just unlocked, stacked at the top of the
+/// screen over everything else until it fades away.
+#[derive(Component)]
+struct Toast(Timer);
+
+/// Holds the toasts on screen, whatever screen that is.
+#[derive(Component)]
+struct ToastStack;
+
+impl Toast {
+    const SECONDS: f32 = 3.0;
+    /// Seconds at the end during which a toast fades out.
+    const FADE_SECONDS: f32 = 0.5;
+
+    fn spawn_stack(mut commands: Commands) {
+        commands.spawn((
+            ToastStack,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(24.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                z_index: ZIndex::Global(2),
+                ..default()
+            },
+        ));
+    }
+
+    fn spawn(
+        mut commands: Commands,
+        mut reader: EventReader<AchievementUnlocked>,
+        stack_query: Query<Entity, With<ToastStack>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let Ok(stack) = stack_query.get_single() else {
+            return;
+        };
+
+        for AchievementUnlocked(achievement) in reader.iter() {
+            commands.entity(stack).with_children(|parent| {
+                parent
+                    .spawn((
+                        Toast(Timer::from_seconds(Toast::SECONDS, TimerMode::Once)),
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                                border: UiRect::all(Val::Px(2.0)),
+                                ..default()
+                            },
+                            border_color: BorderColor(BORDER_COLOR),
+                            background_color: BACKGROUND_COLOR.into(),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "Achievement unlocked",
+                            text_style(&asset_server, 8.0, DETAIL_COLOR),
+                        ));
+                        parent.spawn(TextBundle::from_section(
+                            achievement.name.clone(),
+                            text_style(&asset_server, 12.0, HIGHLIGHT_COLOR),
+                        ));
+                    });
+            });
+        }
+    }
+
+    fn update(
+        mut commands: Commands,
+        mut toast_query: Query<(
+            Entity,
+            &mut Toast,
+            &mut BackgroundColor,
+            &mut BorderColor,
+            &Children,
+        )>,
+        mut text_query: Query<&mut Text>,
+        time: Res<Time>,
+    ) {
+        for (entity, mut toast, mut background, mut border, children) in toast_query.iter_mut() {
+            toast.0.tick(time.delta());
+            if toast.0.finished() {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+
+            let left = toast.0.remaining_secs();
+            if left < Toast::FADE_SECONDS {
+                let alpha = left / Toast::FADE_SECONDS;
+                background.0.set_a(alpha);
+                border.0.set_a(alpha);
+                for &child in children.iter() {
+                    if let Ok(mut text) = text_query.get_mut(child) {
+                        text.sections[0].style.color.set_a(alpha);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct GameOverUI;
+
+impl GameOverUI {
+    /// The restart button, below the high score table if the run just made it.
+    pub fn spawn(
+        commands: &mut Commands,
+        asset_server: &AssetServer,
+        scores: &HighScores,
+        rank: Option<usize>,
+        rounds: Vec<(Player, PlayerRound)>,
+        scoreboard: Scoreboard,
+    ) -> Entity {
+        commands
+            .spawn((
+                UiRoot,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                scoreboard.spawn(parent, asset_server);
+                if rounds.len() > 1 {
+                    ResultsTable::spawn(parent, asset_server, rounds);
+                }
+                if rank.is_some() {
+                    HighScoreTable::spawn(parent, asset_server, scores, rank);
+                }
+                ResetButton::spawn(parent, asset_server);
+            })
+            .id()
+    }
+
+    pub fn on_enter(
+        mut commands: Commands,
+        root_query: Query<Entity, With<UiRoot>>,
+        asset_server: Res<AssetServer>,
+        scores: Res<HighScores>,
+        score: Res<ScoreRes>,
+        pending: Res<PendingHighScore>,
+        bird_query: Query<(&Player, &PlayerRound)>,
+    ) {
+        despawn(&mut commands, root_query);
+
+        // Only a run that is going onto the table can be a new best.
+        let new_best = pending
+            .0
+            .as_ref()
+            .is_some_and(|entry| entry.score > scores.best());
+        let scoreboard = Scoreboard {
+            score: score.0,
+            best: if new_best { score.0 } else { scores.best() },
+            new_best,
+        };
+        commands.insert_resource(scoreboard);
+
+        let rounds = bird_query
+            .iter()
+            .map(|(player, round)| (*player, round.clone()))
+            .collect();
+        let root = match &pending.0 {
+            Some(entry) => NameEntryUI::spawn(&mut commands, &asset_server, entry.score),
+            None => GameOverUI::spawn(
+                &mut commands,
+                &asset_server,
+                &scores,
+                None,
+                rounds,
+                scoreboard,
+            ),
+        };
+        commands.entity(root).insert(SlideIn::default());
+    }
+
+    /// Restarts on the restart button or `Confirm`, once the button has
+    /// appeared.
+    pub fn update(
+        actions: Res<Input<InputAction>>,
+        mut reset_query: Query<(Ref<Interaction>, &mut RestartDelay, &mut Visibility)>,
+        mut next_state: ResMut<NextState<GameState>>,
+        pending: Option<Res<PendingHighScore>>,
+        time: Res<Time>,
+    ) {
+        // Confirming could take a key that is part of the name while one is
+        // being entered.
+        if pending.is_some_and(|pending| pending.0.is_some()) {
+            return;
+        }
+
+        // The button of a name that was just entered is still to be spawned.
+        let mut ready = !reset_query.is_empty();
+        for (interaction, mut delay, mut visibility) in reset_query.iter_mut() {
+            delay.0.tick(time.delta());
+            if !delay.0.finished() {
+                ready = false;
+                visibility.set_if_neq(Visibility::Hidden);
+                continue;
+            }
+
+            visibility.set_if_neq(Visibility::Inherited);
+            if interaction.is_changed() && *interaction == Interaction::Pressed {
                 next_state.set(GameState::Starting);
             }
         }
+
+        if ready && actions.just_pressed(InputAction::Confirm) {
+            next_state.set(GameState::Starting);
+        }
+    }
+}
+
+/// The score of the round against the best score, and the medal it earned.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Scoreboard {
+    score: u32,
+    best: u32,
+    new_best: bool,
+}
+
+impl Scoreboard {
+    const MEDAL_SIZE: f32 = 36.0;
+
+    fn spawn(self, parent: &mut ChildBuilder, asset_server: &AssetServer) {
+        let column = || NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            ..default()
+        };
+        let label = |text: &str| {
+            TextBundle::from_section(text, text_style(asset_server, 10.0, DETAIL_COLOR))
+        };
+
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    column_gap: Val::Px(16.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                border_color: BorderColor(BORDER_COLOR),
+                background_color: BACKGROUND_COLOR.into(),
+                ..default()
+            })
+            .with_children(|parent| {
+                Scoreboard::spawn_medal(parent, asset_server, Medal::for_score(self.score));
+
+                parent.spawn(column()).with_children(|parent| {
+                    parent.spawn(label("Score"));
+                    parent.spawn((
+                        CountUp::new(self.score),
+                        TextBundle::from_section("0", text_style(asset_server, 20.0, BORDER_COLOR)),
+                    ));
+                });
+
+                parent.spawn(column()).with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                column_gap: Val::Px(4.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            if self.new_best {
+                                parent.spawn((
+                                    Reveal,
+                                    TextBundle {
+                                        visibility: Visibility::Hidden,
+                                        ..TextBundle::from_section(
+                                            "New",
+                                            text_style(asset_server, 10.0, HIGHLIGHT_COLOR),
+                                        )
+                                    },
+                                ));
+                            }
+                            parent.spawn(label("Best"));
+                        });
+                    parent.spawn(TextBundle::from_section(
+                        self.best.to_string(),
+                        text_style(asset_server, 20.0, BORDER_COLOR),
+                    ));
+                });
+            });
+    }
+
+    /// The medal, shown once the score has counted up, or an empty slot.
+    fn spawn_medal(parent: &mut ChildBuilder, asset_server: &AssetServer, medal: Option<Medal>) {
+        let mut slot = parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(Scoreboard::MEDAL_SIZE),
+                height: Val::Px(Scoreboard::MEDAL_SIZE),
+                border: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            border_color: BorderColor(DETAIL_COLOR),
+            ..default()
+        });
+
+        let Some(medal) = medal else {
+            return;
+        };
+        slot.with_children(|parent| {
+            parent
+                .spawn((
+                    Reveal,
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: medal_color(medal).into(),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        &medal.label()[..1],
+                        text_style(asset_server, 16.0, BACKGROUND_COLOR),
+                    ));
+                });
+        });
+    }
+}
+
+fn medal_color(medal: Medal) -> Color {
+    match medal {
+        Medal::Bronze => Color::rgb(205. / 255., 127. / 255., 50. / 255.),
+        Medal::Silver => Color::rgb(200. / 255., 200. / 255., 210. / 255.),
+        Medal::Gold => Color::rgb(1., 215. / 255., 0.),
+        Medal::Platinum => Color::rgb(210. / 255., 240. / 255., 250. / 255.),
+    }
+}
+
+/// Shown once the score on screen has finished counting up.
+#[derive(Component)]
+struct Reveal;
+
+/// Counts a number on screen up from zero.
+#[derive(Component)]
+struct CountUp {
+    target: u32,
+    elapsed: f32,
+}
+
+impl CountUp {
+    const PER_SECOND: f32 = 30.0;
+    const MAX_SECONDS: f32 = 1.0;
+
+    fn new(target: u32) -> Self {
+        CountUp {
+            target,
+            elapsed: 0.0,
+        }
+    }
+
+    fn update(
+        mut count_query: Query<(&mut CountUp, &mut Text)>,
+        mut reveal_query: Query<&mut Visibility, With<Reveal>>,
+        time: Res<Time>,
+    ) {
+        for (mut count, mut text) in count_query.iter_mut() {
+            count.elapsed += time.delta_seconds();
+            let seconds = (count.target as f32 / CountUp::PER_SECOND).min(CountUp::MAX_SECONDS);
+            let progress = if seconds > 0.0 {
+                (count.elapsed / seconds).min(1.0)
+            } else {
+                1.0
+            };
+
+            let shown = (count.target as f32 * progress) as u32;
+            let value = shown.to_string();
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+            if progress >= 1.0 {
+                for mut visibility in reveal_query.iter_mut() {
+                    visibility.set_if_neq(Visibility::Inherited);
+                }
+            }
+        }
+    }
+}
+
+/// Drops a screen down into place once it is spawned.
+#[derive(Component, Default)]
+struct SlideIn(f32);
+
+impl SlideIn {
+    const SECONDS: f32 = 0.35;
+    const DISTANCE: f32 = 440.0;
+
+    fn update(
+        mut commands: Commands,
+        mut query: Query<(Entity, &mut SlideIn, &mut Style)>,
+        time: Res<Time>,
+    ) {
+        for (entity, mut slide, mut style) in query.iter_mut() {
+            slide.0 += time.delta_seconds();
+            let progress = (slide.0 / SlideIn::SECONDS).min(1.0);
+            // Eases out, so the screen settles into place.
+            let left = (1.0 - progress).powi(3);
+            style.top = Val::Px(-SlideIn::DISTANCE * left);
+            if progress >= 1.0 {
+                commands.entity(entity).remove::<SlideIn>();
+            }
+        }
+    }
+}
+
+/// Asks for a name for a run that made the high score table.
+struct NameEntryUI;
+
+#[derive(Component)]
+struct NameEntry(String);
+
+impl NameEntryUI {
+    fn spawn(commands: &mut Commands, asset_server: &AssetServer, score: u32) -> Entity {
+        commands
+            .spawn((
+                UiRoot,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(8.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "New High Score!",
+                    text_style(asset_server, 20.0, HIGHLIGHT_COLOR),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    score.to_string(),
+                    text_style(asset_server, 32.0, BORDER_COLOR),
+                ));
+                parent.spawn((
+                    NameEntry(String::new()),
+                    TextBundle::from_section("_", text_style(asset_server, 18.0, BORDER_COLOR)),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    "Type your name, Enter to save",
+                    text_style(asset_server, 10.0, DETAIL_COLOR),
+                ));
+            })
+            .id()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn update(
+        mut commands: Commands,
+        mut entry_query: Query<(&mut NameEntry, &mut Text)>,
+        root_query: Query<Entity, With<UiRoot>>,
+        mut characters: EventReader<ReceivedCharacter>,
+        keyboard: Res<Input<KeyCode>>,
+        mut pending: ResMut<PendingHighScore>,
+        mut scores: ResMut<HighScores>,
+        scoreboard: Res<Scoreboard>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let Ok((mut entry, mut text)) = entry_query.get_single_mut() else {
+            characters.clear();
+            return;
+        };
+
+        for character in characters.iter() {
+            if !character.char.is_control() && entry.0.chars().count() < NAME_LENGTH {
+                entry.0.push(character.char);
+            }
+        }
+        if keyboard.just_pressed(KeyCode::Back) {
+            entry.0.pop();
+        }
+        text.sections[0].value = format!("{}_", entry.0);
+
+        if keyboard.just_pressed(KeyCode::Return) {
+            let rank = pending.submit(&entry.0, &mut scores);
+            despawn(&mut commands, root_query);
+            GameOverUI::spawn(
+                &mut commands,
+                &asset_server,
+                &scores,
+                rank,
+                Vec::new(),
+                *scoreboard,
+            );
+        }
+    }
+}
+
+pub struct LevelCompleteUI;
+
+impl LevelCompleteUI {
+    pub fn spawn(commands: &mut Commands, asset_server: Res<AssetServer>) {
+        commands
+            .spawn((
+                UiRoot,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        justify_content: JustifyContent::Center,
+                        row_gap: Val::Px(12.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "Level Complete",
+                    TextStyle {
+                        font: asset_server.load("Minecraft.ttf"),
+                        font_size: 24.0,
+                        color: BORDER_COLOR,
+                    },
+                ));
+                ResetButton::spawn(parent, &asset_server);
+            });
+    }
+
+    pub fn on_enter(
+        mut commands: Commands,
+        root_query: Query<Entity, With<UiRoot>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        despawn(&mut commands, root_query);
+        LevelCompleteUI::spawn(&mut commands, asset_server);
+    }
+
+    pub fn update(
+        actions: Res<Input<InputAction>>,
+        reset_query: Query<(Ref<Interaction>, &mut RestartDelay, &mut Visibility)>,
+        next_state: ResMut<NextState<GameState>>,
+        time: Res<Time>,
+    ) {
+        GameOverUI::update(actions, reset_query, next_state, None, time);
     }
 }
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use bevy::{
+    app::AppExit, asset::ChangeWatcher, diagnostic::FrameTimeDiagnosticsPlugin, prelude::*,
+    window::WindowResolution,
+};
+
+use crate::{
+    achievement::AchievementPlugin,
+    action::{ActionPlugin, InputAction},
+    highscore::HighScorePlugin,
+    parallax::ParallaxPlugin,
+    pause::PausePlugin,
+    settings::SettingsPlugin,
+    stats::StatsPlugin,
+    theme::ThemePlugin,
+    ui::UiPlugin,
+    FlappyGamePlugins, GameState,
+};
+
+/// The game in a window: the default Bevy plugins, the game itself, the UI and
+/// the themed background. Resources that configure the game, such as a
+/// `ReplayMode`, have to be inserted before this plugin is added.
+pub struct WindowedGamePlugin;
+
+impl Plugin for WindowedGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        resolution: WindowResolution::new(288.0, 440.0),
+                        resizable: false,
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                })
+                .set(AssetPlugin {
+                    watch_for_changes: ChangeWatcher::with_delay(Duration::from_millis(200)),
+                    ..Default::default()
+                }),
+        )
+        .add_plugins(FrameTimeDiagnosticsPlugin)
+        .add_plugins(FlappyGamePlugins)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(ActionPlugin)
+        .add_plugins(HighScorePlugin)
+        .add_plugins(AchievementPlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(ThemePlugin)
+        .add_plugins(ParallaxPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(UiPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, handle_exit_keypress.run_if(back_exits));
+    }
+}
+
+/// During a run `Back` resumes play and leaves the menus instead.
+fn back_exits(state: Res<State<GameState>>) -> bool {
+    !matches!(
+        state.get(),
+        GameState::Playing
+            | GameState::Dying
+            | GameState::Paused
+            | GameState::Settings
+            | GameState::Controls
+    )
+}
+
+fn handle_exit_keypress(actions: Res<Input<InputAction>>, mut exit: EventWriter<AppExit>) {
+    if actions.just_pressed(InputAction::Back) {
+        exit.send(AppExit);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
@@ -0,0 +1,55 @@
+//! Smoke test for gameplay tuning: lets the autopilot play a few seeded runs
+//! headlessly with the rules in `assets/game.rules.ron`, and fails if any of
+//! them ends before clearing the target number of pipes.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use flappy_bevy::{
+    autopilot::AutopilotPlugin, pipe::PipeRng, rules::GameRules, score::ScoreRes,
+    FlappyGamePlugins, GameState, TIMESTEP,
+};
+
+const PIPES: u32 = 100;
+const RUNS: u64 = 5;
+
+#[test]
+fn autopilot_clears_the_pipes_with_the_rules_file() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/game.rules.ron");
+    let text = std::fs::read_to_string(path).expect("the rules file should be readable");
+    let rules: GameRules = ron::from_str(&text).expect("the rules file should parse");
+
+    let failed: Vec<String> = (0..RUNS)
+        .map(|seed| (seed, run(seed, &rules)))
+        .filter(|(_, score)| *score < PIPES)
+        .map(|(seed, score)| format!("seed {}: {} pipes", seed, score))
+        .collect();
+    assert!(
+        failed.is_empty(),
+        "the autopilot didn't clear {} pipes: {}",
+        PIPES,
+        failed.join(", ")
+    );
+}
+
+fn run(seed: u64, rules: &GameRules) -> u32 {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            TIMESTEP,
+        )))
+        .insert_resource(PipeRng::from_seed(seed))
+        .add_plugins(FlappyGamePlugins)
+        .add_plugins(AutopilotPlugin { seed, ..default() })
+        .insert_resource(rules.clone());
+
+    loop {
+        app.update();
+
+        let score = app.world.resource::<ScoreRes>().0;
+        let state = app.world.resource::<State<GameState>>().get();
+        if score >= PIPES || *state == GameState::GameOver {
+            return score;
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! Plays through the menus headlessly with a synthetic gamepad: flaps with a
+//! trigger, pauses with Start, moves between the pause menu and game over
+//! buttons with the D-pad and presses them with the south button, once the
+//! game over screen lets it.
+
+use std::time::Duration;
+
+use bevy::{
+    input::{
+        gamepad::{
+            GamepadButtonChangedEvent, GamepadConnection, GamepadConnectionEvent, GamepadEvent,
+            GamepadInfo,
+        },
+        InputPlugin,
+    },
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+};
+use flappy_bevy::{
+    achievement::AchievementPlugin, action::ActionPlugin, highscore::HighScorePlugin,
+    parallax::ParallaxPlugin, pause::PausePlugin, settings::SettingsPlugin, stats::StatsPlugin,
+    storage, theme::ThemePlugin, ui::UiPlugin, FlappyGamePlugins, GameState, TIMESTEP,
+};
+
+const PAD: Gamepad = Gamepad { id: 0 };
+
+/// Frames to give the bird to fall to the ground.
+const MAX_FRAMES: usize = 1_000;
+
+#[test]
+fn gamepad_drives_the_menus() {
+    // Default bindings, and no high score table left behind.
+    let data_dir = std::env::temp_dir().join(format!("flappy-bevy-gamepad-{}", std::process::id()));
+    std::env::set_var(storage::DATA_DIR_VAR, &data_dir);
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(AssetPlugin::default())
+        .add_plugins(InputPlugin)
+        .add_plugins(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            TIMESTEP,
+        )))
+        .add_plugins(FlappyGamePlugins)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(ActionPlugin)
+        .add_plugins(HighScorePlugin)
+        .add_plugins(AchievementPlugin)
+        .add_plugins(StatsPlugin)
+        .add_plugins(ThemePlugin)
+        .add_plugins(ParallaxPlugin)
+        .add_plugins(PausePlugin)
+        .add_plugins(UiPlugin);
+
+    app.update();
+    app.world
+        .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+            PAD,
+            GamepadConnection::Connected(GamepadInfo {
+                name: "Synthetic pad".to_string(),
+            }),
+        )));
+    app.update();
+
+    press(&mut app, GamepadButtonType::RightTrigger2);
+    expect(&app, GameState::Playing, "the trigger flaps the bird off");
+
+    press(&mut app, GamepadButtonType::Start);
+    expect(&app, GameState::Paused, "Start pauses");
+
+    press(&mut app, GamepadButtonType::DPadDown);
+    press(&mut app, GamepadButtonType::South);
+    expect(&app, GameState::Playing, "Resume is the first button");
+
+    press(&mut app, GamepadButtonType::Start);
+    press(&mut app, GamepadButtonType::DPadDown);
+    press(&mut app, GamepadButtonType::DPadDown);
+    press(&mut app, GamepadButtonType::South);
+    expect(&app, GameState::Starting, "Restart is the second button");
+
+    press(&mut app, GamepadButtonType::South);
+    for _ in 0..MAX_FRAMES {
+        if !matches!(state(&app), GameState::Playing | GameState::Dying) {
+            break;
+        }
+        app.update();
+    }
+    expect(&app, GameState::GameOver, "the bird falls to the ground");
+
+    press(&mut app, GamepadButtonType::South);
+    expect(
+        &app,
+        GameState::GameOver,
+        "a late press doesn't skip the game over screen",
+    );
+
+    // Long enough for the restart button to appear.
+    for _ in 0..60 {
+        app.update();
+    }
+    press(&mut app, GamepadButtonType::DPadDown);
+    press(&mut app, GamepadButtonType::South);
+    expect(
+        &app,
+        GameState::Starting,
+        "the Restart button restarts from game over",
+    );
+
+    let _ = std::fs::remove_dir_all(data_dir);
+}
+
+/// Presses and releases `button`, giving each a frame.
+fn press(app: &mut App, button: GamepadButtonType) {
+    for value in [1.0, 0.0] {
+        app.world
+            .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+                PAD, button, value,
+            )));
+        app.update();
+    }
+    // State transitions are applied on the next tick.
+    app.update();
+}
+
+fn state(app: &App) -> GameState {
+    *app.world.resource::<State<GameState>>().get()
+}
+
+fn expect(app: &App, expected: GameState, step: &str) {
+    assert_eq!(state(app), expected, "{}", step);
+}
@@ -0,0 +1,166 @@
+//! Drives a headless game over the remote control protocol from a client
+//! thread, the way an external controller would.
+
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use flappy_bevy::{
+    remote::{RemoteAddr, RemoteControl},
+    FlappyGamePlugins, TIMESTEP,
+};
+use serde_json::Value;
+
+struct Controller {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Controller {
+    /// Connects and returns the first frame the game sends.
+    fn connect(addr: &RemoteAddr) -> (Controller, Value) {
+        let RemoteAddr::Tcp(addr) = addr else {
+            panic!("the game should listen on TCP");
+        };
+        let writer = TcpStream::connect(addr).expect("the game should accept controllers");
+        let mut controller = Controller {
+            reader: BufReader::new(writer.try_clone().unwrap()),
+            writer,
+        };
+        let frame = controller.read().expect("the game should send a frame");
+        (controller, frame)
+    }
+
+    fn read(&mut self) -> Option<Value> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(serde_json::from_str(&line).expect("frames should be JSON")),
+        }
+    }
+
+    /// Sends `command` and returns the frame of the tick after it.
+    fn send(&mut self, command: &str) -> Value {
+        writeln!(self.writer, "{}", command).unwrap();
+        self.read().expect("the game should send the next frame")
+    }
+}
+
+/// The frames seen right after each step of the script.
+struct Frames {
+    start: Vec<Value>,
+    launch: Value,
+    falling: Value,
+    unknown: Value,
+    flap: Value,
+    reconnected: Value,
+    flap_again: Value,
+    reset: Value,
+}
+
+fn number(frame: &Value, key: &str) -> f64 {
+    frame[key]
+        .as_f64()
+        .unwrap_or_else(|| panic!("{} should be a number", key))
+}
+
+#[test]
+fn controller_drives_the_bird() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            TIMESTEP,
+        )));
+
+    let remote = RemoteControl::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = remote.addr().clone();
+    app.insert_resource(remote).add_plugins(FlappyGamePlugins);
+
+    let done = Arc::new(AtomicBool::new(false));
+    let client = thread::spawn({
+        let done = done.clone();
+        move || {
+            let (mut controller, first) = Controller::connect(&addr);
+            let mut start = vec![first];
+            for _ in 0..5 {
+                start.push(controller.send("noop"));
+            }
+            let launch = controller.send("flap");
+            let mut falling = controller.send("noop");
+            while falling["bird_velocity"].as_f64() >= Some(0.) {
+                falling = controller.send("noop");
+            }
+            let unknown = controller.send("dive");
+            let flap = controller.send("flap");
+            drop(controller);
+
+            let (mut controller, reconnected) = Controller::connect(&addr);
+            let flap_again = controller.send("flap");
+            let reset = controller.send("reset");
+            done.store(true, Ordering::SeqCst);
+
+            // Keep the game going until the test stops updating it.
+            while writeln!(controller.writer, "noop").is_ok() && controller.read().is_some() {}
+
+            Frames {
+                start,
+                launch,
+                falling,
+                unknown,
+                flap,
+                reconnected,
+                flap_again,
+                reset,
+            }
+        }
+    });
+
+    while !done.load(Ordering::SeqCst) {
+        app.update();
+    }
+    // Closing the socket lets the client stop waiting for frames.
+    drop(app);
+    let frames = client.join().expect("the client should finish");
+
+    // The bird waits for its first flap.
+    for frame in &frames.start {
+        assert_eq!(frame["state"], "Starting");
+    }
+    let ticks: Vec<_> = frames
+        .start
+        .iter()
+        .map(|frame| number(frame, "tick"))
+        .collect();
+    assert!(
+        ticks.windows(2).all(|pair| pair[1] == pair[0] + 1.),
+        "every command should advance one tick: {:?}",
+        ticks
+    );
+
+    assert_eq!(frames.launch["state"], "Playing");
+
+    // Unknown commands are ignored like `noop`, so the bird keeps falling.
+    assert_eq!(frames.unknown["state"], "Playing");
+    assert!(number(&frames.unknown, "bird_velocity") < number(&frames.falling, "bird_velocity"));
+
+    assert_eq!(frames.flap["state"], "Playing");
+    assert!(number(&frames.flap, "bird_velocity") > 0.);
+
+    // A new controller picks up the same run.
+    assert_eq!(frames.reconnected["state"], "Playing");
+    assert!(number(&frames.reconnected, "tick") > number(&frames.flap, "tick"));
+    assert!(number(&frames.reconnected, "bird_y") > number(&frames.flap, "bird_y"));
+    assert!(
+        number(&frames.flap_again, "bird_velocity") > number(&frames.reconnected, "bird_velocity")
+    );
+
+    assert_eq!(frames.reset["state"], "Starting");
+}
@@ -0,0 +1,113 @@
+//! Records a seeded headless run, plays the recording back at another frame
+//! rate, and checks that both runs end on the same tick with the same score.
+
+use std::{path::PathBuf, time::Duration};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use flappy_bevy::{
+    bird::{Bird, PendingJump, Player},
+    pipe::{Gap, Pipe, PipeRng},
+    replay::{Replay, ReplayMode},
+    score::ScoreRes,
+    FlappyGamePlugins, GameState, SimulationTick, TIMESTEP,
+};
+
+/// Frames to give a run before giving up on it ending.
+const MAX_FRAMES: usize = 20_000;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("flappy-bevy-{}-{}", std::process::id(), name))
+}
+
+fn app(frame: f32) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            frame,
+        )));
+    app
+}
+
+fn state(app: &App) -> GameState {
+    *app.world.resource::<State<GameState>>().get()
+}
+
+/// Flaps whenever the bird is below the middle of the next gap, the way a
+/// player's input would arrive between frames.
+fn steer(app: &mut App) {
+    let world = &mut app.world;
+    let pipes: Vec<_> = world
+        .query::<(Entity, &Pipe)>()
+        .iter(world)
+        .map(|(entity, pipe)| (entity, pipe.translation()))
+        .collect();
+    let target = Gap::upcoming(pipes).first().map_or(0., Gap::center);
+    let y = world
+        .query::<&Bird>()
+        .iter(world)
+        .next()
+        .map_or(0., |bird| bird.translation().y);
+
+    if y < target - 10. {
+        world.resource_mut::<PendingJump>().set(Player::ONE);
+    }
+}
+
+/// Runs the app until the round is over, calling `before` ahead of each frame.
+fn finish(app: &mut App, mut before: impl FnMut(&mut App)) -> (u32, u64) {
+    for _ in 0..MAX_FRAMES {
+        before(app);
+        app.update();
+        if state(app) == GameState::GameOver {
+            let score = app.world.resource::<ScoreRes>().0;
+            let tick = app.world.resource::<SimulationTick>().0;
+            return (score, tick);
+        }
+    }
+    panic!("the round didn't end within {} frames", MAX_FRAMES);
+}
+
+#[test]
+fn playback_repeats_the_recorded_run() {
+    let path = temp_path("run.replay.ron");
+
+    let mut recording = app(TIMESTEP);
+    recording
+        .insert_resource(PipeRng::from_seed(7))
+        .insert_resource(ReplayMode::record(&path))
+        .add_plugins(FlappyGamePlugins);
+    let recorded = finish(&mut recording, steer);
+
+    let ReplayMode::Record { replay, .. } = recording.world.resource::<ReplayMode>() else {
+        panic!("the recording app should stay recording");
+    };
+    let saved = Replay::load(&path).expect("the replay should be saved");
+    assert_eq!(&saved, replay);
+    assert_eq!((saved.score, saved.end), recorded);
+    assert!(saved.score > 0, "the recorded run should pass some pipes");
+
+    // Several ticks to a frame, unlike the recording.
+    let mut playback = app(TIMESTEP * 2.5);
+    playback
+        .insert_resource(ReplayMode::playback(saved))
+        .add_plugins(FlappyGamePlugins);
+    let played = finish(&mut playback, |_| {});
+
+    assert_eq!(played, recorded);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn replays_survive_saving_and_loading() {
+    let path = temp_path("round-trip.replay.ron");
+    let replay = Replay {
+        seed: 42,
+        jumps: vec![1, 30, 31, 95],
+        score: 3,
+        end: 400,
+    };
+
+    replay.save(&path).expect("the replay should save");
+    assert_eq!(Replay::load(&path).expect("the replay should load"), replay);
+    let _ = std::fs::remove_file(path);
+}